@@ -5,7 +5,9 @@ use std::sync::atomic::Ordering::SeqCst;
 use anyhow::Error;
 use bitflags::bitflags;
 use log::{warn};
+use tokio::sync::broadcast;
 use ntrim_tools::tokiort;
+use crate::events::BotEvent;
 use crate::client::qsecurity::QSecurity;
 use crate::client::trpc::TrpcClient;
 use crate::servlet::olpush::OlPushServlet;
@@ -30,6 +32,8 @@ pub struct Bot {
     pub client: Arc<TrpcClient>,
     /// Bot status.
     pub status: AtomicU32,
+    /// Event bus.
+    event_sender: broadcast::Sender<BotEvent>,
 }
 
 impl Bot {
@@ -39,11 +43,15 @@ impl Bot {
     ) -> Result<Arc<Self>, Error> {
         let unique_id = session.uin;
         let client = TrpcClient::new(session, qsec_mod).await?;
+        let event_queue_size = std::env::var("EVENT_QUEUE_SIZE")
+            .map_or(1024, |v| v.parse::<usize>().unwrap());
+        let (event_sender, _) = broadcast::channel(event_queue_size);
 
         let bot = Arc::new(Self {
             unique_id,
             client,
             status: AtomicU32::new(BotStatus::Offline.bits()),
+            event_sender,
         });
         RegisterProxyServlet::initialize(&bot).await;
         OlPushServlet::initialize(&bot).await;
@@ -78,6 +86,16 @@ impl Bot {
         self.client.is_connected().await &&
             BotStatus::from_bits(self.status.load(SeqCst)).unwrap().contains(BotStatus::Online)
     }
+
+    /// 订阅消息、通知和请求事件
+    pub fn subscribe(&self) -> broadcast::Receiver<BotEvent> {
+        self.event_sender.subscribe()
+    }
+
+    pub(crate) fn post_event(&self, event: BotEvent) {
+        // 没有订阅者时发送会失败，直接丢弃即可
+        let _ = self.event_sender.send(event);
+    }
 }
 
 impl fmt::Debug for Bot {
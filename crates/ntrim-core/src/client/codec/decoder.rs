@@ -117,7 +117,13 @@ async fn loop_decode(trpc: &Arc<TrpcClient>) {
         let body = match compression {
             0 => body,
             4 => body,
-            1 => decompress_deflate(&body),
+            1 => match decompress_deflate(&body) {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Failed to decompress packet, cmd: {}, seq: {}, err: {}", cmd, seq, e);
+                    return;
+                }
+            },
             _ => body
         };
 
@@ -1,4 +1,5 @@
 pub mod get_profile_detail;
 pub mod set_profile_detail;
 pub mod get_summary_card;
-mod vote_user;
\ No newline at end of file
+mod vote_user;
+mod send_poke;
\ No newline at end of file
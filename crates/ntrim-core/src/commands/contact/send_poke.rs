@@ -0,0 +1,28 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::pb::oidb::Ded3ReqBody;
+
+struct SendPokeCodec;
+
+#[command("OidbSvcTrpcTcp.0xed3_1", "_send_poke", Service, Protobuf)]
+impl SendPokeCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        group_id: Option<i64>,
+        friend_uin: Option<i64>,
+        target: i64
+    ) -> Option<Vec<u8>> {
+        let body = Ded3ReqBody {
+            to_uin: Some(target),
+            group_code: group_id,
+            friend_uin,
+            ext: Some(0),
+        };
+        oidb_request!(0xed3, 1, body.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<bool> {
+        oidb_response!(0xed3, 1, data.as_slice()).map(|_| true)
+    }
+}
@@ -0,0 +1,46 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::pb::oidb::{D6d6ReqBody, D6d6RspBody, DownloadFileReqBody};
+
+struct GetGroupFileUrlCodec;
+
+#[command("OidbSvcTrpcTcp.0x6d6_2", "_get_group_file_url", Service, Protobuf)]
+impl GetGroupFileUrlCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        group_id: i64,
+        file_id: String,
+        bus_id: u32
+    ) -> Option<Vec<u8>> {
+        let body = D6d6ReqBody {
+            download_file_req: Some(DownloadFileReqBody {
+                group_code: Some(group_id),
+                app_id: Some(7),
+                bus_id: Some(bus_id),
+                file_id: Some(file_id),
+            }),
+        };
+        oidb_request!(0x6d6, 2, body.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<String> {
+        let response = oidb_response!(0x6d6, 2, data.as_slice())?;
+        let rsp = match D6d6RspBody::decode(response.as_slice()) {
+            Ok(rsp) => rsp.download_file_rsp?,
+            Err(e) => {
+                error!("Failed to decode D6d6RspBody: {:?}, data: {}", e, hex::encode(&response));
+                return None;
+            }
+        };
+        if rsp.ret_code.unwrap_or(0) != 0 {
+            error!("Failed to get group file url, code: {:?}, msg: {:?}", rsp.ret_code, rsp.client_wording);
+            return None;
+        }
+        Some(format!(
+            "https://{}/ftn_handler/{}/?fname=",
+            rsp.download_dns.unwrap_or_default(),
+            hex::encode(rsp.download_url.unwrap_or_default())
+        ))
+    }
+}
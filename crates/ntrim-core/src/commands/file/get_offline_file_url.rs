@@ -0,0 +1,50 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::pb::oidb::{De37ApplyDownloadReq, De37ReqBody, De37RspBody};
+
+struct GetOfflineFileUrlCodec;
+
+#[command("OidbSvcTrpcTcp.0xe37_1200", "_get_offline_file_url", Service, Protobuf)]
+impl GetOfflineFileUrlCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        receiver_uid: String,
+        file_uuid: String,
+        file_hash: String
+    ) -> Option<Vec<u8>> {
+        let body = De37ReqBody {
+            sub_command: Some(1200),
+            field2: Some(1),
+            apply_download_req: Some(De37ApplyDownloadReq {
+                receiver_uid: Some(receiver_uid),
+                file_uuid: Some(file_uuid),
+                r#type: Some(2),
+                file_hash: Some(file_hash),
+                t2: Some(0),
+            }),
+            field101: Some(3),
+            field102: Some(103),
+            field200: Some(1),
+            field99999: Some(vec![0xc0, 0x85, 0x2c, 0x01]),
+        };
+        oidb_request!(0xe37, 1200, body.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<String> {
+        let response = oidb_response!(0xe37, 1200, data.as_slice())?;
+        let info = match De37RspBody::decode(response.as_slice()) {
+            Ok(rsp) => rsp.apply_download_rsp?.download_info?,
+            Err(e) => {
+                error!("Failed to decode De37RspBody: {:?}, data: {}", e, hex::encode(&response));
+                return None;
+            }
+        };
+        Some(format!(
+            "http://{}:{}{}&isthumb=0",
+            info.download_domain.unwrap_or_default(),
+            info.download_port.unwrap_or(80),
+            info.download_url.unwrap_or_default()
+        ))
+    }
+}
@@ -0,0 +1,2 @@
+mod get_group_file_url;
+mod get_offline_file_url;
@@ -0,0 +1,74 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::pb::structmsg::{FlagInfo, ReqSystemMsgNew, RspSystemMsgNew};
+
+/// 好友系统消息（好友申请）
+#[derive(Debug, Default, Clone)]
+pub struct FriendSystemMsg {
+    pub msg_seq: i64,
+    pub msg_time: i64,
+    pub req_uin: i64,
+    pub req_nick: String,
+    /// 验证消息
+    pub comment: String,
+    /// 来源
+    pub source: String,
+    /// 是否已经处理
+    pub decided: bool,
+}
+
+impl FriendSystemMsg {
+    /// 处理好友申请时使用的flag
+    pub fn flag(&self) -> String {
+        format!("{}:{}", self.req_uin, self.msg_seq)
+    }
+}
+
+struct GetFriendSystemMsgCodec;
+
+#[command("ProfileService.Pb.ReqSystemMsgNew.Friend", "_get_friend_system_msg", Protobuf, Service)]
+impl GetFriendSystemMsgCodec {
+    async fn generate(bot: &Arc<Bot>, latest_friend_seq: i64) -> Option<Vec<u8>> {
+        let req = ReqSystemMsgNew {
+            msg_num: 20,
+            latest_friend_seq,
+            version: 1000,
+            flag: Some(FlagInfo {
+                frd_msg_discuss2_many_chat: 1,
+                frd_msg_get_busi_card: 1,
+                frd_msg_need_waiting_msg: 1,
+                frd_msg_uint32_need_all_unread_msg: 1,
+                grp_msg_mask_invite_auto_join: 1,
+                ..Default::default()
+            }),
+            friend_msg_type_flag: 1,
+            ..Default::default()
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<Vec<FriendSystemMsg>> {
+        let rsp = RspSystemMsgNew::decode(data.as_slice()).ok()?;
+        if let Some(head) = rsp.head.as_ref() {
+            if head.result != 0 {
+                warn!("Failed to get friend system msg: {}, {}", head.result, head.msg_fail);
+                return None;
+            }
+        }
+        Some(rsp.friendmsgs
+            .into_iter()
+            .filter_map(|m| {
+                let msg = m.msg?;
+                Some(FriendSystemMsg {
+                    msg_seq: m.msg_seq,
+                    msg_time: m.msg_time,
+                    req_uin: m.req_uin,
+                    req_nick: msg.req_uin_nick,
+                    comment: msg.msg_additional,
+                    source: msg.msg_source,
+                    decided: !msg.msg_decided.is_empty(),
+                })
+            })
+            .collect())
+    }
+}
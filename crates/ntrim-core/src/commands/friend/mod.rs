@@ -1,5 +1,8 @@
 pub mod get_friend_group_list;
+pub mod get_friend_system_msg;
+mod set_friend_system_msg;
 
 pub use get_friend_group_list::FriendListResponse;
 pub use get_friend_group_list::FriendInfo;
-pub use get_friend_group_list::FriendGroupInfo;
\ No newline at end of file
+pub use get_friend_group_list::FriendGroupInfo;
+pub use get_friend_system_msg::FriendSystemMsg;
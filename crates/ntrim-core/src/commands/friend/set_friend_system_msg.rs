@@ -0,0 +1,43 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::pb::structmsg::{ReqSystemMsgAction, RspSystemMsgAction, SystemMsgActionInfo};
+
+struct SetFriendSystemMsgCodec;
+
+#[command("ProfileService.Pb.ReqSystemMsgAction.Friend", "_set_friend_system_msg", Protobuf, Service)]
+impl SetFriendSystemMsgCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        req_uin: i64,
+        msg_seq: i64,
+        approve: bool,
+        remark: String
+    ) -> Option<Vec<u8>> {
+        let req = ReqSystemMsgAction {
+            msg_type: 1,
+            msg_seq,
+            req_uin,
+            sub_type: 1,
+            src_id: 6,
+            sub_src_id: 7,
+            action_info: Some(SystemMsgActionInfo {
+                r#type: if approve { 2 } else { 3 },
+                remark,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<bool> {
+        let rsp = RspSystemMsgAction::decode(data.as_slice()).ok()?;
+        match rsp.head {
+            Some(head) if head.result != 0 => {
+                warn!("Failed to handle friend request: {}, {}", head.result, head.msg_fail);
+                Some(false)
+            }
+            _ => Some(true)
+        }
+    }
+}
@@ -7,6 +7,7 @@ pub mod msg_svc;
 pub mod troop;
 pub mod friend;
 mod contact;
+mod file;
 
 /// timeout不可以小于5s时间，否则可能导致内存泄露
 #[macro_export]
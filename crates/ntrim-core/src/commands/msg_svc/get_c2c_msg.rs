@@ -0,0 +1,38 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::pb::trpc::olpush;
+use crate::pb::trpc::register::{SsoGetC2cMsg, SsoGetC2cMsgRsp};
+
+struct GetC2cMsgCodec;
+
+#[command("trpc.msg.register_proxy.RegisterProxy.SsoGetC2cMsg", "_get_c2c_msg", Protobuf, Service)]
+impl GetC2cMsgCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        friend_uid: String,
+        start_seq: i64,
+        end_seq: i64
+    ) -> Option<Vec<u8>> {
+        let req = SsoGetC2cMsg {
+            friend_uid,
+            start_seq: start_seq as u64,
+            end_seq: end_seq as u64,
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<Vec<olpush::Message>> {
+        let rsp = match SsoGetC2cMsgRsp::decode(data.as_slice()) {
+            Ok(rsp) => rsp,
+            Err(e) => {
+                error!("Failed to decode SsoGetC2cMsgRsp: {:?}, data: {}", e, hex::encode(&data));
+                return None;
+            }
+        };
+        if rsp.retcode.unwrap_or(0) != 0 {
+            error!("Failed to get c2c msg, code: {:?}, msg: {:?}", rsp.retcode, rsp.err_msg);
+            return None;
+        }
+        Some(rsp.messages)
+    }
+}
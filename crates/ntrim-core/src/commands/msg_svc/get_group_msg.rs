@@ -0,0 +1,41 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::pb::trpc::olpush;
+use crate::pb::trpc::register::{SsoGetGroupMsg, SsoGetGroupMsgInfo, SsoGetGroupMsgRsp};
+
+struct GetGroupMsgCodec;
+
+#[command("trpc.msg.register_proxy.RegisterProxy.SsoGetGroupMsg", "_get_group_msg", Protobuf, Service)]
+impl GetGroupMsgCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        group_id: i64,
+        start_seq: i64,
+        end_seq: i64
+    ) -> Option<Vec<u8>> {
+        let req = SsoGetGroupMsg {
+            info: SsoGetGroupMsgInfo {
+                group_uin: group_id as u64,
+                start_seq: start_seq as u64,
+                end_seq: end_seq as u64,
+            },
+            direction: Some(true),
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<Vec<olpush::Message>> {
+        let body = match SsoGetGroupMsgRsp::decode(data.as_slice()) {
+            Ok(rsp) => rsp.body?,
+            Err(e) => {
+                error!("Failed to decode SsoGetGroupMsgRsp: {:?}, data: {}", e, hex::encode(&data));
+                return None;
+            }
+        };
+        if body.retcode.unwrap_or(0) != 0 {
+            error!("Failed to get group msg, code: {:?}, msg: {:?}", body.retcode, body.err_msg);
+            return None;
+        }
+        Some(body.messages)
+    }
+}
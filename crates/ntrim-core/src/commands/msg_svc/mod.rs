@@ -1 +1,7 @@
-pub mod send_raw_msg;
\ No newline at end of file
+pub mod send_raw_msg;
+mod get_group_msg;
+mod get_c2c_msg;
+mod send_long_msg;
+mod recv_long_msg;
+mod recall_group_msg;
+mod recall_c2c_msg;
\ No newline at end of file
@@ -0,0 +1,49 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::pb::msg::{C2cRecallMsgReq, RecallMsgRsp};
+use crate::pb::msg::c2c_recall_msg_req::{C2cRecallMsgInfo, C2cRecallMsgSettings};
+
+struct RecallC2cMsgCodec;
+
+#[command("trpc.msg.msg_svc.MsgService.SsoC2CRecallMsg", "_recall_c2c_msg", Protobuf, Service)]
+impl RecallC2cMsgCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        uid: String,
+        client_seq: i64,
+        msg_seq: i64,
+        msg_uid: i64,
+        msg_time: i64
+    ) -> Option<Vec<u8>> {
+        let req = C2cRecallMsgReq {
+            r#type: 1,
+            target_uid: uid,
+            info: C2cRecallMsgInfo {
+                client_seq,
+                // msg_uid的低32位就是发送时的random
+                random: msg_uid as u32,
+                msg_uid,
+                msg_time,
+                field5: Some(0),
+                msg_seq,
+            },
+            settings: Some(C2cRecallMsgSettings {
+                field1: Some(false),
+                field2: Some(0),
+            }),
+            field6: Some(false),
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<bool> {
+        let rsp = RecallMsgRsp::decode(data.as_slice()).ok()?;
+        match rsp.result {
+            Some(result) if result != 0 => {
+                warn!("Failed to recall c2c msg: {}, {}", result, rsp.err_msg.unwrap_or_default());
+                Some(false)
+            }
+            _ => Some(true)
+        }
+    }
+}
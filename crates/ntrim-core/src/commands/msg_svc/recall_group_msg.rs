@@ -0,0 +1,36 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::pb::msg::{GroupRecallMsgReq, RecallMsgRsp};
+use crate::pb::msg::group_recall_msg_req::{GroupRecallMsgInfo, GroupRecallMsgSettings};
+
+struct RecallGroupMsgCodec;
+
+#[command("trpc.msg.msg_svc.MsgService.SsoGroupRecallMsg", "_recall_group_msg", Protobuf, Service)]
+impl RecallGroupMsgCodec {
+    async fn generate(bot: &Arc<Bot>, group_id: i64, msg_seq: i64, random: u32) -> Option<Vec<u8>> {
+        let req = GroupRecallMsgReq {
+            r#type: 1,
+            group_id,
+            info: GroupRecallMsgInfo {
+                msg_seq,
+                random: Some(random),
+                field3: Some(0),
+            },
+            settings: Some(GroupRecallMsgSettings {
+                field1: Some(false),
+            }),
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<bool> {
+        let rsp = RecallMsgRsp::decode(data.as_slice()).ok()?;
+        match rsp.result {
+            Some(result) if result != 0 => {
+                warn!("Failed to recall group msg: {}, {}", result, rsp.err_msg.unwrap_or_default());
+                Some(false)
+            }
+            _ => Some(true)
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use prost::Message;
+use ntrim_macros::command;
+use ntrim_tools::flate2::decompress_gzip;
+use crate::pb::trpc::long_msg::{ * };
+use crate::pb::trpc::olpush;
+
+struct RecvLongMsgCodec;
+
+#[command("trpc.group.long_msg_interface.MsgService.SsoRecvLongMsg", "_recv_long_msg", Protobuf, Service)]
+impl RecvLongMsgCodec {
+    async fn generate(bot: &Arc<Bot>, res_id: String) -> Option<Vec<u8>> {
+        let uid = bot.client.session.read().await.uid.clone();
+        let req = RecvLongMsgReq {
+            info: Some(RecvLongMsgInfo {
+                uid: Some(LongMsgUid {
+                    uid: Some(uid),
+                }),
+                res_id: Some(res_id),
+                acquire: Some(true),
+            }),
+            settings: Some(LongMsgSettings {
+                field1: Some(2),
+                field2: Some(0),
+                field3: Some(0),
+                field4: Some(0),
+            }),
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<Vec<olpush::Message>> {
+        let payload = match RecvLongMsgRsp::decode(data.as_slice()) {
+            Ok(rsp) => rsp.result?.payload?,
+            Err(e) => {
+                error!("Failed to decode RecvLongMsgRsp: {:?}, data: {}", e, hex::encode(&data));
+                return None;
+            }
+        };
+        let payload = match decompress_gzip(&payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to decompress long msg payload: {:?}", e);
+                return None;
+            }
+        };
+        let result = match LongMsgResult::decode(payload.as_slice()) {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to decode LongMsgResult: {:?}", e);
+                return None;
+            }
+        };
+        Some(result.action.into_iter()
+            .filter(|action| action.action_command.as_deref() == Some("MultiMsg"))
+            .filter_map(|action| action.action_data)
+            .flat_map(|content| content.msg_body)
+            .collect())
+    }
+}
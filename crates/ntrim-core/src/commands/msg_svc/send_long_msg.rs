@@ -0,0 +1,55 @@
+use prost::Message;
+use ntrim_macros::command;
+use ntrim_tools::flate2::compress_gzip;
+use crate::pb::trpc::long_msg::{ * };
+use crate::pb::trpc::olpush;
+
+struct SendLongMsgCodec;
+
+#[command("trpc.group.long_msg_interface.MsgService.SsoSendLongMsg", "_send_long_msg", Protobuf, Service)]
+impl SendLongMsgCodec {
+    /// `group_id`为0时`uid`为好友的uid
+    async fn generate(
+        bot: &Arc<Bot>,
+        group_id: i64,
+        uid: String,
+        messages: Vec<olpush::Message>
+    ) -> Option<Vec<u8>> {
+        let payload = LongMsgResult {
+            action: vec![LongMsgAction {
+                action_command: Some("MultiMsg".to_string()),
+                action_data: Some(LongMsgContent {
+                    msg_body: messages
+                }),
+            }],
+        }.encode_to_vec();
+        let is_group = group_id != 0;
+        let req = SendLongMsgReq {
+            info: Some(SendLongMsgInfo {
+                r#type: Some(if is_group { 3 } else { 1 }),
+                uid: Some(LongMsgUid {
+                    uid: Some(if is_group { group_id.to_string() } else { uid }),
+                }),
+                group_uin: if is_group { Some(group_id) } else { None },
+                payload: Some(compress_gzip(&payload)),
+            }),
+            settings: Some(LongMsgSettings {
+                field1: Some(4),
+                field2: Some(1),
+                field3: Some(7),
+                field4: Some(0),
+            }),
+        };
+        Some(req.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<String> {
+        match SendLongMsgRsp::decode(data.as_slice()) {
+            Ok(rsp) => rsp.result?.res_id,
+            Err(e) => {
+                error!("Failed to decode SendLongMsgRsp: {:?}, data: {}", e, hex::encode(&data));
+                None
+            }
+        }
+    }
+}
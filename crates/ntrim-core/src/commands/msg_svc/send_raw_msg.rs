@@ -4,7 +4,6 @@ use std::sync::atomic::AtomicU32;
 use bytes::Bytes;
 use once_cell::sync::Lazy;
 use prost::Message;
-use rand::{Rng, thread_rng};
 use ntrim_macros::command;
 use crate::pb::msg::send_msg_req::{ContentHead, RoutingHead};
 use crate::pb::msg::{MessageBody, RichText, SendMsgReq, SendMsgRsp};
@@ -16,27 +15,35 @@ impl SendMsgCodec {
     async fn generate(
         bot: &Arc<Bot>,
         contact: RoutingHead,
-        rich_text: RichText
+        rich_text: RichText,
+        pkg_num: u64,
+        pkg_index: u32,
+        div_seq: u32,
+        client_seq: u32,
+        random: u32
     ) -> Option<Vec<u8>> {
         let send_msg = SendMsgReq {
             routing_head: contact,
+            // 分片发送时所有分片的div_seq相同
             content_head: ContentHead {
-                pkg_num: 1,
-                pkg_index: 0,
-                div_seq: 0
+                pkg_num,
+                pkg_index,
+                div_seq
             },
             msg_body: MessageBody {
-                rich_text: Some(rich_text)
+                rich_text: Some(rich_text),
+                msg_content: None
             },
-            msg_seq: next_msg_seq(bot.unique_id) as u64,
-            msg_time: thread_rng().gen_range(1700000000 .. 3100000000),
+            msg_seq: client_seq as u64,
+            // 这个字段实际上是random，撤回的时候要用
+            msg_time: random as u64,
             via: 0
         };
         //info!("Generated a message: {:?}", hex::encode(&send_msg.encode_to_vec()));
         Some(send_msg.encode_to_vec())
     }
 
-    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<u64> {
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<SendMsgRsp> {
         //info!("Sent message successfully, seq: {}", hex::encode(&data));
         let data = Bytes::from(data);
         let send_msg = SendMsgRsp::decode(data.as_ref()).ok()?;
@@ -48,11 +55,12 @@ impl SendMsgCodec {
         //    error!("Failed to send message, reason: account is under wind control");
         //    return None;
         //}
-        return Some(send_msg.msg_seq.unwrap_or(0));
+        return Some(send_msg);
     }
 }
 
-fn next_msg_seq(uin: i64) -> u32 {
+/// 发消息时的client_seq，撤回私聊消息的时候要用
+pub(crate) fn next_msg_seq(uin: i64) -> u32 {
     static MAP_SEQ: Lazy<Mutex<HashMap<i64, AtomicU32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
     let mut map_seq = MAP_SEQ.lock().unwrap();
     let seq = map_seq.entry(uin).or_insert(AtomicU32::new(17050));
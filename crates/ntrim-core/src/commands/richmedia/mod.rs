@@ -1,11 +1,23 @@
 use std::collections::HashMap;
 use std::sync::atomic::AtomicU32;
 use std::sync::Mutex;
+use prost::Message;
 use once_cell::sync::Lazy;
+use crate::pb::msg::{ExtBizInfo, IndexNode};
+use crate::pb::trpc::rich_media_ntv2::{ * };
 
 pub mod request_download_rkey;
 pub mod request_upload_resource;
+mod request_upload_c2c_pic;
+mod request_upload_group_ptt;
+mod request_upload_c2c_ptt;
+mod request_upload_group_video;
+mod request_upload_c2c_video;
 mod request_upload_ukey;
+mod request_group_video_url;
+mod request_c2c_video_url;
+mod request_group_ptt_url;
+mod request_c2c_ptt_url;
 
 pub const SCENE_UNKNOWN: u32 = 0;
 pub const SCENE_C2C: u32 = 1;
@@ -13,6 +25,7 @@ pub const SCENE_GROUP: u32 = 2;
 pub const SCENE_CHANNEL: u32 = 3;
 
 pub const SUB_FILE_TYPE_PIC: u32 = 0;
+pub const SUB_FILE_TYPE_THUMB: u32 = 100;
 
 pub fn next_rich_media_seq(uin: i64) -> u32 {
     static MAP_RICH_MEDIA: Lazy<Mutex<HashMap<i64, AtomicU32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
@@ -22,4 +35,154 @@ pub fn next_rich_media_seq(uin: i64) -> u32 {
         seq.store(1, std::sync::atomic::Ordering::Relaxed);
     }
     seq.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+pub const BUSINESS_PIC: u32 = 1;
+pub const BUSINESS_VIDEO: u32 = 2;
+pub const BUSINESS_PTT: u32 = 3;
+
+/// 构建NTv2富媒体上传请求(cmd = 100)
+pub(crate) fn build_upload_req(
+    bot_uin: i64,
+    business_type: u32,
+    c2c: Option<C2cUserInfo>,
+    grp: Option<GroupUserInfo>,
+    channel: Option<ChannelUserInfo>,
+    upload_info: Vec<UploadInfo>,
+    ext_biz_info: ExtBizInfo
+) -> NtV2RichMediaReq {
+    let scene_type = if c2c.is_some() {
+        SCENE_C2C
+    } else if grp.is_some() {
+        SCENE_GROUP
+    } else if channel.is_some() {
+        SCENE_CHANNEL
+    } else {
+        SCENE_UNKNOWN
+    };
+    NtV2RichMediaReq {
+        head: MultiMediaReqHead {
+            head: CommonHead {
+                req_id: next_rich_media_seq(bot_uin),
+                cmd: 100,
+                msg: None,
+            },
+            scene: SceneInfo {
+                request_type: 2,
+                business_type,
+                app_type: None,
+                scene_type: Some(scene_type),
+                c2c,
+                grp,
+                channel,
+                byte_arr: None,
+            },
+            client_meta: ClientMeta {
+                agent_type: 2,
+            },
+        },
+        upload: Some(UploadReq {
+            no_need_compat_msg: Some(true),
+            client_seq: Some(28321),
+            ext_biz_info: Some(ext_biz_info),
+            compat_q_msg_scene_type: Some(scene_type),
+            client_random_id: Some(1009114165),
+            srv_send_msg: Some(false),
+            try_fast_upload_completed: Some(true),
+            upload_info
+        }),
+        download_req: None,
+        download: None,
+    }
+}
+
+pub(crate) fn parse_upload_rsp(data: &[u8]) -> Option<UploadRsp> {
+    match NtV2RichMediaRsp::decode(data) {
+        Ok(v) => {
+            if (v.head.ret_code.is_none() || v.head.ret_code == Some(0)) && v.upload.is_some() {
+                v.upload
+            } else {
+                log::error!("Failed to request upload resource, code: {:?}, msg: {}", v.head.ret_code, v.head.msg);
+                None
+            }
+        },
+        Err(e) => {
+            log::error!("Failed to decode NtV2RichMediaRsp(100): {:?}, data: {}", e, hex::encode(data));
+            None
+        }
+    }
+}
+
+/// 构建NTv2富媒体下载请求(cmd = 200)
+pub(crate) fn build_download_req(
+    bot_uin: i64,
+    request_type: u32,
+    business_type: u32,
+    c2c: Option<C2cUserInfo>,
+    grp: Option<GroupUserInfo>,
+    node: IndexNode
+) -> NtV2RichMediaReq {
+    let scene_type = if c2c.is_some() { SCENE_C2C } else { SCENE_GROUP };
+    NtV2RichMediaReq {
+        head: MultiMediaReqHead {
+            head: CommonHead {
+                req_id: next_rich_media_seq(bot_uin),
+                cmd: 200,
+                msg: None,
+            },
+            scene: SceneInfo {
+                request_type,
+                business_type,
+                app_type: None,
+                scene_type: Some(scene_type),
+                c2c,
+                grp,
+                channel: None,
+                byte_arr: None,
+            },
+            client_meta: ClientMeta {
+                agent_type: 2,
+            },
+        },
+        upload: None,
+        download_req: Some(DownloadReq {
+            node,
+            ext: Some(match business_type {
+                BUSINESS_VIDEO => DownloadExt {
+                    pic: None,
+                    video: Some(VideoDownloadExt {
+                        busi_type: Some(0),
+                        scene_type: Some(0),
+                    }),
+                    ptt: None,
+                },
+                BUSINESS_PTT => DownloadExt { pic: None, video: None, ptt: Some(PttDownloadExt {}) },
+                _ => DownloadExt { pic: Some(PicDownloadExt {}), video: None, ptt: None },
+            }),
+        }),
+        download: None,
+    }
+}
+
+/// 从下载响应里面拼接完整的下载链接
+pub(crate) fn parse_download_url(data: &[u8]) -> Option<String> {
+    let rsp = match NtV2RichMediaRsp::decode(data) {
+        Ok(rsp) => rsp,
+        Err(e) => {
+            log::error!("Failed to decode NtV2RichMediaRsp(200): {:?}, data: {}", e, hex::encode(data));
+            return None;
+        }
+    };
+    if rsp.head.ret_code.is_some_and(|code| code != 0) {
+        log::error!("Failed to request download url, code: {:?}, msg: {}", rsp.head.ret_code, rsp.head.msg);
+        return None;
+    }
+    let download = rsp.download?;
+    let info = download.info?;
+    Some(format!(
+        "https://{}{}{}",
+        info.domain.unwrap_or_default(),
+        info.url_path.unwrap_or_default(),
+        download.rkey_param.unwrap_or_default()
+    ))
 }
\ No newline at end of file
@@ -0,0 +1,21 @@
+use prost::Message as _;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::commands::richmedia::{build_download_req, parse_download_url, BUSINESS_PTT};
+use crate::pb::msg::IndexNode;
+use crate::pb::trpc::rich_media_ntv2::C2cUserInfo;
+
+struct RequestC2cPttUrlCodec;
+
+#[command("OidbSvcTrpcTcp.0x126d_200", "_request_c2c_ptt_url", Protobuf, Service)]
+impl RequestC2cPttUrlCodec {
+    async fn generate(bot: &Arc<Bot>, uid: String, node: IndexNode) -> Option<Vec<u8>> {
+        let c2c = C2cUserInfo { account_type: 2, uid, byte_arr: None };
+        oidb_request!(0x126d, 200, build_download_req(bot.unique_id, 1, BUSINESS_PTT, Some(c2c), None, node).encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<String> {
+        let response = oidb_response!(0x126d, 200, data.as_slice())?;
+        parse_download_url(response.as_slice())
+    }
+}
@@ -27,6 +27,7 @@ impl RequestDownloadRKeyCodec {
                 },
             },
             upload: None,
+            download_req: None,
             download: Some(DownloadRkeyReq {
                 types: vec![10, 20],
                 download_type: Some(2),
@@ -0,0 +1,21 @@
+use prost::Message as _;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::commands::richmedia::{build_download_req, parse_download_url, BUSINESS_PTT};
+use crate::pb::msg::IndexNode;
+use crate::pb::trpc::rich_media_ntv2::GroupUserInfo;
+
+struct RequestGroupPttUrlCodec;
+
+#[command("OidbSvcTrpcTcp.0x126e_200", "_request_group_ptt_url", Protobuf, Service)]
+impl RequestGroupPttUrlCodec {
+    async fn generate(bot: &Arc<Bot>, group_id: i64, node: IndexNode) -> Option<Vec<u8>> {
+        let grp = GroupUserInfo { uin: group_id as u32 };
+        oidb_request!(0x126e, 200, build_download_req(bot.unique_id, 1, BUSINESS_PTT, None, Some(grp), node).encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<String> {
+        let response = oidb_response!(0x126e, 200, data.as_slice())?;
+        parse_download_url(response.as_slice())
+    }
+}
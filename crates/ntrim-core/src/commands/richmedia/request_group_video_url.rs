@@ -0,0 +1,21 @@
+use prost::Message as _;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::commands::richmedia::{build_download_req, parse_download_url, BUSINESS_VIDEO};
+use crate::pb::msg::IndexNode;
+use crate::pb::trpc::rich_media_ntv2::GroupUserInfo;
+
+struct RequestGroupVideoUrlCodec;
+
+#[command("OidbSvcTrpcTcp.0x11ea_200", "_request_group_video_url", Protobuf, Service)]
+impl RequestGroupVideoUrlCodec {
+    async fn generate(bot: &Arc<Bot>, group_id: i64, node: IndexNode) -> Option<Vec<u8>> {
+        let grp = GroupUserInfo { uin: group_id as u32 };
+        oidb_request!(0x11ea, 200, build_download_req(bot.unique_id, 2, BUSINESS_VIDEO, None, Some(grp), node).encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<String> {
+        let response = oidb_response!(0x11ea, 200, data.as_slice())?;
+        parse_download_url(response.as_slice())
+    }
+}
@@ -0,0 +1,39 @@
+use prost::Message as _;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::commands::richmedia::{ * };
+use crate::pb::msg::ExtBizInfo;
+
+struct RequestUploadC2cVideoCodec;
+
+#[command("OidbSvcTrpcTcp.0x11e9_100", "_request_upload_c2c_video", Protobuf, Service)]
+impl RequestUploadC2cVideoCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        uid: String,
+        video_info: FileInfo,
+        thumb_info: FileInfo,
+        ext_biz_info: ExtBizInfo
+    ) -> Option<Vec<u8>> {
+        oidb_request!(0x11e9, 100, build_upload_req(
+            bot.unique_id,
+            BUSINESS_VIDEO,
+            Some(C2cUserInfo { account_type: 2, uid, byte_arr: None }),
+            None,
+            None,
+            vec![UploadInfo {
+                sub_file_type: SUB_FILE_TYPE_PIC,
+                file_info: video_info
+            }, UploadInfo {
+                sub_file_type: SUB_FILE_TYPE_THUMB,
+                file_info: thumb_info
+            }],
+            ext_biz_info
+        ).encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<UploadRsp> {
+        let response = oidb_response!(0x11e9, 100, data.as_slice())?;
+        parse_upload_rsp(response.as_slice())
+    }
+}
@@ -0,0 +1,30 @@
+use prost::Message as _;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::commands::richmedia::{ * };
+use crate::pb::msg::ExtBizInfo;
+
+struct RequestUploadGroupPttCodec;
+
+#[command("OidbSvcTrpcTcp.0x126e_100", "_request_upload_group_ptt", Protobuf, Service)]
+impl RequestUploadGroupPttCodec {
+    async fn generate(bot: &Arc<Bot>, group_id: i64, file_info: FileInfo, ext_biz_info: ExtBizInfo) -> Option<Vec<u8>> {
+        oidb_request!(0x126e, 100, build_upload_req(
+            bot.unique_id,
+            BUSINESS_PTT,
+            None,
+            Some(GroupUserInfo { uin: group_id as u32 }),
+            None,
+            vec![UploadInfo {
+                sub_file_type: SUB_FILE_TYPE_PIC,
+                file_info
+            }],
+            ext_biz_info
+        ).encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<UploadRsp> {
+        let response = oidb_response!(0x126e, 100, data.as_slice())?;
+        parse_upload_rsp(response.as_slice())
+    }
+}
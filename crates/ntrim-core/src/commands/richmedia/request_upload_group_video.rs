@@ -0,0 +1,39 @@
+use prost::Message as _;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::commands::richmedia::{ * };
+use crate::pb::msg::ExtBizInfo;
+
+struct RequestUploadGroupVideoCodec;
+
+#[command("OidbSvcTrpcTcp.0x11ea_100", "_request_upload_group_video", Protobuf, Service)]
+impl RequestUploadGroupVideoCodec {
+    async fn generate(
+        bot: &Arc<Bot>,
+        group_id: i64,
+        video_info: FileInfo,
+        thumb_info: FileInfo,
+        ext_biz_info: ExtBizInfo
+    ) -> Option<Vec<u8>> {
+        oidb_request!(0x11ea, 100, build_upload_req(
+            bot.unique_id,
+            BUSINESS_VIDEO,
+            None,
+            Some(GroupUserInfo { uin: group_id as u32 }),
+            None,
+            vec![UploadInfo {
+                sub_file_type: SUB_FILE_TYPE_PIC,
+                file_info: video_info
+            }, UploadInfo {
+                sub_file_type: SUB_FILE_TYPE_THUMB,
+                file_info: thumb_info
+            }],
+            ext_biz_info
+        ).encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<UploadRsp> {
+        let response = oidb_response!(0x11ea, 100, data.as_slice())?;
+        parse_upload_rsp(response.as_slice())
+    }
+}
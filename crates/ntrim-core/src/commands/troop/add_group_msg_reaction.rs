@@ -0,0 +1,25 @@
+use prost::Message;
+use ntrim_macros::command;
+use crate::{oidb_request, oidb_response};
+use crate::pb::oidb::D9082ReqBody;
+
+struct AddGroupMsgReactionCodec;
+
+#[command("OidbSvcTrpcTcp.0x9082_1", "_add_group_msg_reaction", Service, Protobuf)]
+impl AddGroupMsgReactionCodec {
+    async fn generate(bot: &Arc<Bot>, group_id: i64, msg_seq: i64, code: String, r#type: u32) -> Option<Vec<u8>> {
+        let body = D9082ReqBody {
+            group_code: Some(group_id),
+            msg_seq: Some(msg_seq),
+            code: Some(code),
+            r#type: Some(r#type),
+            field6: Some(false),
+            field7: Some(false),
+        };
+        oidb_request!(0x9082, 1, body.encode_to_vec())
+    }
+
+    async fn parse(bot: &Arc<Bot>, data: Vec<u8>) -> Option<bool> {
+        oidb_response!(0x9082, 1, data.as_slice()).map(|_| true)
+    }
+}
@@ -3,6 +3,8 @@ pub mod get_troop_member_list;
 mod get_troop_simple_info;
 mod get_troop_info;
 mod get_troop_member_card_info;
+mod add_group_msg_reaction;
+mod del_group_msg_reaction;
 
 pub use get_troop_list::GroupInfo;
 pub use get_troop_member_list::GroupMemberInfo;
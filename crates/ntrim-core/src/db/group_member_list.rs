@@ -1,7 +1,7 @@
 use std::fmt::format;
 use anyhow::Error;
 use sqlx::{PgPool, Row};
-use crate::commands::troop::GroupMemberInfo;
+use crate::commands::troop::{GroupMemberInfo, GroupMemberPermission};
 use crate::commands::troop::GroupMemberPermission::{Administrator, Member, Owner};
 
 const TABLE_NAME: &'static str = "group_member_list";
@@ -140,4 +140,43 @@ impl GroupMemberInfo {
             ..Default::default()
         })
     }
+
+    pub async fn query_uin_by_uid(pool: &PgPool, group_id: i64, uid: &str) -> Result<i64, Error> {
+        let row = sqlx::query(format!("SELECT uin FROM {} WHERE group_id = $1 AND uid = $2", TABLE_NAME).as_str())
+            .bind(group_id)
+            .bind(uid)
+            .fetch_one(pool)
+            .await?;
+        Ok(row.get("uin"))
+    }
+
+    pub async fn update_permission(pool: &PgPool, group_id: i64, uin: i64, permission: GroupMemberPermission) -> Result<(), Error> {
+        sqlx::query(format!("UPDATE {} SET permission = $1 WHERE group_id = $2 AND uin = $3", TABLE_NAME).as_str())
+            .bind(permission as i32)
+            .bind(group_id)
+            .bind(uin)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_special_title(pool: &PgPool, group_id: i64, uin: i64, special_title: &str) -> Result<(), Error> {
+        sqlx::query(format!("UPDATE {} SET special_title = $1 WHERE group_id = $2 AND uin = $3", TABLE_NAME).as_str())
+            .bind(special_title)
+            .bind(group_id)
+            .bind(uin)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_shut_up_timestamp(pool: &PgPool, group_id: i64, uin: i64, shut_up_timestamp: i64) -> Result<(), Error> {
+        sqlx::query(format!("UPDATE {} SET shut_up_timestamp = $1 WHERE group_id = $2 AND uin = $3", TABLE_NAME).as_str())
+            .bind(shut_up_timestamp)
+            .bind(group_id)
+            .bind(uin)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
 }
\ No newline at end of file
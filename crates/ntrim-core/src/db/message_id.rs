@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use anyhow::Error;
+use sqlx::{PgPool, Row};
+use sqlx::postgres::PgRow;
+use crate::bot::Bot;
+use crate::service::msg::MessageId;
+use crate::service::msg::message_id::contact_key;
+use crate::servlet::olpush::msg::Contact;
+
+const TABLE_NAME: &str = "message_ids";
+
+impl MessageId {
+    pub async fn create_table(pool: &PgPool) -> Result<(), Error> {
+        sqlx::query(format!("CREATE TABLE IF NOT EXISTS {} ( \
+            id INTEGER NOT NULL, \
+            receiver BIGINT NOT NULL, \
+            contact_type VARCHAR(50) NOT NULL, \
+            contact_uin BIGINT NOT NULL, \
+            contact_uid VARCHAR(255) NOT NULL, \
+            msg_seq BIGINT NOT NULL, \
+            client_seq BIGINT NOT NULL DEFAULT 0, \
+            msg_uid BIGINT NOT NULL, \
+            msg_time BIGINT NOT NULL, \
+            PRIMARY KEY (receiver, id) \
+        )", TABLE_NAME).as_str()).execute(pool).await?;
+        sqlx::query(format!("CREATE INDEX IF NOT EXISTS {0}_seq_idx ON {0} (receiver, contact_type, contact_uin, msg_seq)", TABLE_NAME).as_str())
+            .execute(pool).await?;
+        Ok(())
+    }
+
+    /// id已经被占用时返回false
+    pub async fn insert(pool: &PgPool, bot: &Arc<Bot>, message_id: &MessageId) -> Result<bool, Error> {
+        let (contact_type, contact_uin) = contact_key(&message_id.contact);
+        let contact_uid = match &message_id.contact {
+            Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) => uid.as_str(),
+            Contact::Group(..) => "",
+        };
+        let result = sqlx::query(format!(r#"
+            INSERT INTO "{}" ("id", "receiver", "contact_type", "contact_uin", "contact_uid", "msg_seq", "client_seq", "msg_uid", "msg_time")
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT ("receiver", "id") DO NOTHING
+        "#, TABLE_NAME).as_str())
+            .bind(message_id.id)
+            .bind(bot.unique_id)
+            .bind(contact_type)
+            .bind(contact_uin)
+            .bind(contact_uid)
+            .bind(message_id.msg_seq)
+            .bind(message_id.client_seq)
+            .bind(message_id.msg_uid)
+            .bind(message_id.msg_time)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_client_seq(pool: &PgPool, bot: &Arc<Bot>, id: i32, client_seq: i64) -> Result<(), Error> {
+        sqlx::query(format!(r#"
+            UPDATE "{}" SET client_seq = $1
+            WHERE id = $2 AND receiver = $3
+        "#, TABLE_NAME).as_str())
+            .bind(client_seq)
+            .bind(id)
+            .bind(bot.unique_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_by_id(pool: &PgPool, bot: &Arc<Bot>, id: i32) -> Result<Option<MessageId>, Error> {
+        let row = sqlx::query(format!(r#"
+            SELECT id, contact_type, contact_uin, contact_uid, msg_seq, client_seq, msg_uid, msg_time
+            FROM "{}"
+            WHERE id = $1 AND receiver = $2
+        "#, TABLE_NAME).as_str())
+            .bind(id)
+            .bind(bot.unique_id)
+            .fetch_optional(pool)
+            .await?;
+        row.map(|row| parse_row(&row)).transpose()
+    }
+
+    pub async fn get_by_seq(pool: &PgPool, bot: &Arc<Bot>, contact: &Contact, msg_seq: i64) -> Result<Option<MessageId>, Error> {
+        let (contact_type, contact_uin) = contact_key(contact);
+        let row = sqlx::query(format!(r#"
+            SELECT id, contact_type, contact_uin, contact_uid, msg_seq, client_seq, msg_uid, msg_time
+            FROM "{}"
+            WHERE receiver = $1 AND contact_type = $2 AND contact_uin = $3 AND msg_seq = $4
+            LIMIT 1
+        "#, TABLE_NAME).as_str())
+            .bind(bot.unique_id)
+            .bind(contact_type)
+            .bind(contact_uin)
+            .bind(msg_seq)
+            .fetch_optional(pool)
+            .await?;
+        row.map(|row| parse_row(&row)).transpose()
+    }
+}
+
+fn parse_row(row: &PgRow) -> Result<MessageId, Error> {
+    let uin = row.get::<i64, _>("contact_uin");
+    let uid = row.get::<String, _>("contact_uid");
+    let contact = match row.get::<String, _>("contact_type").as_str() {
+        "group" => Contact::Group("".to_string(), uin),
+        "friend" => Contact::Friend("".to_string(), uin, uid),
+        "stranger" => Contact::Stranger("".to_string(), uin, uid),
+        _ => return Err(Error::msg("unknown contact type")),
+    };
+    Ok(MessageId {
+        id: row.get("id"),
+        contact,
+        msg_seq: row.get("msg_seq"),
+        client_seq: row.get("client_seq"),
+        msg_uid: row.get("msg_uid"),
+        msg_time: row.get("msg_time"),
+    })
+}
@@ -2,13 +2,16 @@ use std::sync::Arc;
 use anyhow::Error;
 use bytes::Bytes;
 use prost::Message;
-use serde_json::from_slice;
 use sqlx::{PgPool, Row};
+use sqlx::postgres::PgRow;
 use crate::bot::Bot;
 use crate::pb::msg::RichText;
 use crate::servlet::olpush::msg::{Contact, MessageRecord};
+use crate::servlet::olpush::msg::decoder::{parse_elements, ParseMode};
 
 const TABLE_NAME: &'static str = "messages";
+const SELECT_COLUMNS: &str = "contact_type, contact_name, contact_uin, contact_uid, sender_id, sender_uid, sender_nick, \
+    sender_unique_title, msg_time, msg_seq, msg_uid, receiver, elements, is_self";
 
 impl MessageRecord {
     pub async fn create_table(pool: &PgPool) -> Result<(), Error> {
@@ -32,8 +35,16 @@ impl MessageRecord {
                 msg_seq BIGINT NOT NULL, \
                 msg_uid BIGINT NOT NULL UNIQUE, \
                 receiver BIGINT NOT NULL, \
-                elements BYTEA \
+                elements BYTEA, \
+                recalled BOOLEAN NOT NULL DEFAULT FALSE, \
+                is_self BOOLEAN NOT NULL DEFAULT FALSE \
             )", TABLE_NAME).as_str()).execute(pool).await?;
+        } else {
+            // 旧版本创建的表没有撤回标记和同步消息标记
+            sqlx::query(format!("ALTER TABLE {} ADD COLUMN IF NOT EXISTS recalled BOOLEAN NOT NULL DEFAULT FALSE", TABLE_NAME).as_str())
+                .execute(pool).await?;
+            sqlx::query(format!("ALTER TABLE {} ADD COLUMN IF NOT EXISTS is_self BOOLEAN NOT NULL DEFAULT FALSE", TABLE_NAME).as_str())
+                .execute(pool).await?;
         }
         Ok(())
     }
@@ -45,8 +56,8 @@ impl MessageRecord {
             Contact::Stranger(name, id, uid) => ("stranger", name, *id as i64, uid.as_str())
         };
         sqlx::query(format!(r#"
-            INSERT INTO "{}" ("contact_type", "contact_name", "contact_uin", "contact_uid", "sender_id", "sender_uid", "sender_nick", "sender_unique_title", "msg_time", "msg_seq", "msg_uid", "receiver", "elements")
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            INSERT INTO "{}" ("contact_type", "contact_name", "contact_uin", "contact_uid", "sender_id", "sender_uid", "sender_nick", "sender_unique_title", "msg_time", "msg_seq", "msg_uid", "receiver", "elements", "is_self")
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             ON CONFLICT ("msg_uid") DO UPDATE SET
                 "contact_type" = EXCLUDED."contact_type",
                 "contact_name" = EXCLUDED."contact_name",
@@ -60,7 +71,8 @@ impl MessageRecord {
                 "msg_seq" = EXCLUDED."msg_seq",
                 "msg_uid" = EXCLUDED."msg_uid",
                 "receiver" = EXCLUDED."receiver",
-                "elements" = EXCLUDED."elements"
+                "elements" = EXCLUDED."elements",
+                "is_self" = EXCLUDED."is_self"
         "#, TABLE_NAME).as_str())
             .bind(r#type)
             .bind(name)
@@ -75,37 +87,55 @@ impl MessageRecord {
             .bind(message.msg_uid as i64)
             .bind(bot.unique_id)
             .bind(raw_elems)
+            .bind(message.is_self)
             .execute(pool)
             .await?;
         Ok(())
     }
 
     pub async fn get_message_by_uid(pool: &PgPool, bot: &Arc<Bot>, msg_uid: u64) -> Result<MessageRecord, Error> {
-        let row = sqlx::query(
-            r#"
-        SELECT
-            contact_type,
-            contact_name,
-            contact_uin,
-            contact_uid,
-            sender_id,
-            sender_uid,
-            sender_nick,
-            sender_unique_title,
-            msg_time,
-            msg_seq,
-            msg_uid,
-            receiver,
-            elements
-        FROM "TABLE_NAME"
-        WHERE msg_uid = $1 AND receiver = $2
-        "#,
-        )
+        let row = sqlx::query(format!(r#"
+            SELECT {}
+            FROM "{}"
+            WHERE msg_uid = $1 AND receiver = $2
+        "#, SELECT_COLUMNS, TABLE_NAME).as_str())
             .bind(msg_uid as i64)
             .bind(bot.unique_id)
             .fetch_one(pool)
             .await?;
+        Self::from_row(bot, row).await
+    }
+
+    /// 按seq范围查询消息，`contact_type`为group/friend/stranger，结果按seq升序
+    pub async fn get_messages_by_seq(
+        pool: &PgPool,
+        bot: &Arc<Bot>,
+        contact_type: &str,
+        contact_uin: i64,
+        start_seq: i64,
+        end_seq: i64
+    ) -> Result<Vec<MessageRecord>, Error> {
+        let rows = sqlx::query(format!(r#"
+            SELECT {}
+            FROM "{}"
+            WHERE contact_type = $1 AND contact_uin = $2 AND msg_seq BETWEEN $3 AND $4 AND receiver = $5
+            ORDER BY msg_seq ASC
+        "#, SELECT_COLUMNS, TABLE_NAME).as_str())
+            .bind(contact_type)
+            .bind(contact_uin)
+            .bind(start_seq)
+            .bind(end_seq)
+            .bind(bot.unique_id)
+            .fetch_all(pool)
+            .await?;
+        let mut records = Vec::with_capacity(rows.len());
+        for row in rows {
+            records.push(Self::from_row(bot, row).await?);
+        }
+        Ok(records)
+    }
 
+    async fn from_row(bot: &Arc<Bot>, row: PgRow) -> Result<MessageRecord, Error> {
         let name = row.get("contact_name");
         let id = row.get::<i64, _>("contact_uin");
         let uid = row.try_get("contact_uid").unwrap_or("".to_string());
@@ -126,12 +156,43 @@ impl MessageRecord {
             msg_time: row.get("msg_time"),
             msg_seq: row.get::<i64, _>("msg_seq"),
             msg_uid: row.get::<i64, _>("msg_uid"),
-            elements: Vec::new(), // assuming elements is a JSON array, handle deserialization properly
+            is_self: row.get("is_self"),
+            is_history: true,
+            elements: Vec::new(),
         };
-        let rich_text: Vec<u8> = from_slice(&row.get::<Vec<u8>, _>("elements")).unwrap_or_default();
-        let rich_text = RichText::decode(Bytes::from(rich_text)).unwrap();
-        crate::servlet::olpush::msg::decoder::parse_elements(&bot, &mut record, rich_text.elems).await;
+        // elements保存的是原始的RichText
+        let rich_text: Vec<u8> = row.try_get("elements").unwrap_or_default();
+        let rich_text = RichText::decode(Bytes::from(rich_text))?;
+        // 数据库里的消息离线解析，不需要连接
+        parse_elements(bot, &mut record, rich_text.elems, ParseMode::Offline).await;
         Ok(record)
     }
 
-}
\ No newline at end of file
+    /// 标记群消息已撤回，返回被撤回消息的msg_uid
+    pub async fn set_group_msg_recalled(pool: &PgPool, bot: &Arc<Bot>, group_id: i64, msg_seq: i64) -> Result<Option<i64>, Error> {
+        let row = sqlx::query(format!(r#"
+            UPDATE "{}" SET "recalled" = TRUE
+            WHERE "contact_type" = 'group' AND "contact_uin" = $1 AND "msg_seq" = $2 AND "receiver" = $3
+            RETURNING "msg_uid"
+        "#, TABLE_NAME).as_str())
+            .bind(group_id)
+            .bind(msg_seq)
+            .bind(bot.unique_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(row.map(|row| row.get::<i64, _>("msg_uid")))
+    }
+
+    /// 通过msg_uid标记消息已撤回
+    pub async fn set_msg_recalled(pool: &PgPool, bot: &Arc<Bot>, msg_uid: i64) -> Result<bool, Error> {
+        let result = sqlx::query(format!(r#"
+            UPDATE "{}" SET "recalled" = TRUE
+            WHERE "msg_uid" = $1 AND "receiver" = $2
+        "#, TABLE_NAME).as_str())
+            .bind(msg_uid)
+            .bind(bot.unique_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -3,6 +3,7 @@ pub mod message_record;
 pub mod group_list;
 mod group_member_list;
 mod friend_list;
+mod message_id;
 
 use std::sync::OnceLock;
 use sqlx::{Acquire, PgPool};
@@ -11,6 +12,7 @@ use crate::commands::friend::{FriendListResponse};
 use crate::commands::troop::{GroupInfo, GroupMemberInfo};
 pub use crate::db::simple_record::SimpleMessageRecord;
 use crate::MessageRecord;
+use crate::service::msg::MessageId;
 
 pub static PG_POOL: OnceLock<PgPool> = OnceLock::new();
 
@@ -42,6 +44,7 @@ pub async fn ensure_table_exists() -> Result<(), anyhow::Error> {
         GroupInfo::create_table(pool),
         GroupMemberInfo::create_table(pool),
         FriendListResponse::create_table(pool),
+        MessageId::create_table(pool),
     )?;
     Ok(())
 }
\ No newline at end of file
@@ -61,4 +61,18 @@ impl SimpleMessageRecord {
             .await?;
         Ok(message)
     }
+
+    /// 收到新的群消息时更新最后看到的seq
+    pub async fn update_seq(pool: &PgPool, id: i64, seq: i64, latest_msg_time: Option<NaiveDateTime>) -> Result<(), Error> {
+        sqlx::query(format!(r#"
+            UPDATE "{}" SET "seq" = $2, "latest_msg_time" = COALESCE($3, "latest_msg_time")
+            WHERE "id" = $1 AND "seq" < $2
+        "#, TABLE_NAME).as_str())
+            .bind(id)
+            .bind(seq)
+            .bind(latest_msg_time)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
 }
\ No newline at end of file
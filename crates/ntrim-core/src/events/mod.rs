@@ -1 +1,20 @@
-pub mod wtlogin_event;
\ No newline at end of file
+pub mod wtlogin_event;
+pub mod notice_event;
+pub mod request_event;
+
+use std::sync::Arc;
+use crate::servlet::olpush::msg::MessageRecord;
+
+pub use notice_event::{NoticeEvent, GroupHonor};
+pub use request_event::RequestEvent;
+
+/// 推送给后端的事件，通过`Bot::subscribe`订阅
+#[derive(Debug, Clone)]
+pub enum BotEvent {
+    /// 消息事件
+    Message(Arc<MessageRecord>),
+    /// 通知事件
+    Notice(NoticeEvent),
+    /// 请求事件
+    Request(RequestEvent),
+}
@@ -0,0 +1,136 @@
+#[derive(Debug, Clone)]
+pub enum NoticeEvent {
+    /// 新增好友（包括单向好友）
+    FriendAdd {
+        uin: i64,
+        uid: String,
+        nick: String,
+        time: i64,
+    },
+    /// 群消息撤回，`msg_uid`未知时为0
+    GroupRecall {
+        group_id: i64,
+        operator_uin: i64,
+        operator_uid: String,
+        sender_uin: i64,
+        sender_uid: String,
+        msg_seq: i64,
+        msg_uid: i64,
+        time: i64,
+    },
+    /// 群消息表情回应，`count`为回应之后这个表情的总数
+    GroupReaction {
+        group_id: i64,
+        operator_uin: i64,
+        operator_uid: String,
+        msg_seq: i64,
+        emoji_id: String,
+        count: u32,
+        /// 为true时添加回应，否则取消回应
+        is_add: bool,
+        time: i64,
+    },
+    /// 好友消息撤回，`uin`为好友，`operator_uin`为撤回者
+    FriendRecall {
+        uin: i64,
+        uid: String,
+        operator_uin: i64,
+        msg_seq: i64,
+        msg_uid: i64,
+        time: i64,
+    },
+    /// 好友昵称变更
+    FriendNickChange {
+        uin: i64,
+        nick: String,
+        time: i64,
+    },
+    /// 好友被删除
+    FriendDelete {
+        uin: i64,
+        time: i64,
+    },
+    /// 群禁言，`target_uin`为0时为全员禁言，`duration`为0时为解除禁言
+    GroupMute {
+        group_id: i64,
+        operator_uin: i64,
+        operator_uid: String,
+        target_uin: i64,
+        target_uid: String,
+        /// 禁言时长(秒)
+        duration: i64,
+        time: i64,
+    },
+    /// 群管理员变更
+    GroupAdminChange {
+        group_id: i64,
+        uin: i64,
+        uid: String,
+        /// 为true时设置管理员，否则取消管理员
+        is_admin: bool,
+        time: i64,
+    },
+    /// 群头衔变更
+    GroupSpecialTitleChange {
+        group_id: i64,
+        uin: i64,
+        special_title: String,
+        time: i64,
+    },
+    /// 戳一戳，`group_id`为0时为好友戳一戳
+    Poke {
+        group_id: i64,
+        sender_uin: i64,
+        target_uin: i64,
+        /// 动作，例如“戳了戳”
+        action: String,
+        /// 后缀，例如“的脸”
+        suffix: String,
+        time: i64,
+    },
+    /// 群荣誉变更
+    GroupHonorChange {
+        group_id: i64,
+        uin: i64,
+        honor: GroupHonor,
+        time: i64,
+    },
+    /// 其它群灰条提示
+    GroupGrayTip {
+        group_id: i64,
+        templ_id: u64,
+        content: String,
+        time: i64,
+    },
+    /// 群文件上传，下载链接通过`Bot::get_group_file_url`获取
+    GroupFileUpload {
+        group_id: i64,
+        uin: i64,
+        uid: String,
+        file_id: String,
+        name: String,
+        size: i64,
+        busid: u32,
+        time: i64,
+    },
+    /// 好友离线文件，下载链接通过`Bot::get_offline_file_url`获取
+    OfflineFile {
+        uin: i64,
+        uid: String,
+        file_id: String,
+        file_hash: String,
+        name: String,
+        size: i64,
+        time: i64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupHonor {
+    /// 龙王
+    Talkative,
+    /// 群聊之火
+    Performer,
+    /// 快乐源泉
+    Emotion,
+}
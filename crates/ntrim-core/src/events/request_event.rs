@@ -0,0 +1,15 @@
+#[derive(Debug, Clone)]
+pub enum RequestEvent {
+    /// 好友申请，使用`flag`调用`Bot::set_friend_add_request`处理
+    FriendRequest {
+        uin: i64,
+        uid: String,
+        nick: String,
+        /// 验证消息
+        comment: String,
+        /// 来源
+        source: String,
+        flag: String,
+        time: i64,
+    },
+}
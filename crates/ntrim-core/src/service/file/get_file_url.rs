@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use anyhow::Error;
+use crate::await_response;
+use crate::bot::Bot;
+
+impl Bot {
+    /// 获取群文件下载链接，`bus_id`来自群文件上传通知
+    pub async fn get_group_file_url(self: &Arc<Self>, group_id: i64, file_id: String, bus_id: u32) -> Result<String, Error> {
+        await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = Bot::_get_group_file_url(self, group_id, file_id, bus_id).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to get_group_file_url: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to get group file url"))
+    }
+
+    /// 获取好友离线文件下载链接，`file_id`与`file_hash`来自离线文件通知
+    pub async fn get_offline_file_url(self: &Arc<Self>, file_id: String, file_hash: String) -> Result<String, Error> {
+        let self_uid = self.client.session.read().await.uid.clone();
+        await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = Bot::_get_offline_file_url(self, self_uid, file_id, file_hash).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to get_offline_file_url: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to get offline file url"))
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use anyhow::Error;
+use crate::await_response;
+use crate::bot::Bot;
+use crate::commands::friend::FriendSystemMsg;
+
+impl Bot {
+    /// 获取待处理的好友申请
+    pub async fn get_friend_system_msg(self: &Arc<Self>) -> Result<Vec<FriendSystemMsg>, Error> {
+        await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = Bot::_get_friend_system_msg(self, 0).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to get_friend_system_msg: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("GetFriendSystemMsg result as null"))
+    }
+
+    /// 处理好友申请，`flag`来自好友申请事件，为`uin`或者`uin:seq`
+    pub async fn set_friend_add_request(
+        self: &Arc<Self>,
+        flag: &str,
+        approve: bool,
+        remark: Option<String>
+    ) -> Result<(), Error> {
+        let (req_uin, msg_seq) = match flag.split_once(':') {
+            Some((uin, seq)) => (uin.parse::<i64>().ok(), seq.parse::<i64>().ok()),
+            None => (flag.parse::<i64>().ok(), None),
+        };
+        let req_uin = req_uin.ok_or(Error::msg(format!("Invalid friend request flag: {}", flag)))?;
+        let msg_seq = match msg_seq {
+            Some(msg_seq) => msg_seq,
+            None => Bot::get_friend_system_msg(self).await?
+                .into_iter()
+                .filter(|m| m.req_uin == req_uin && !m.decided)
+                .max_by_key(|m| m.msg_seq)
+                .map(|m| m.msg_seq)
+                .ok_or(Error::msg(format!("Friend request from {} not found", req_uin)))?,
+        };
+        let result = await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = Bot::_set_friend_system_msg(self, req_uin, msg_seq, approve, remark.unwrap_or_default()).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to set_friend_add_request: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("SetFriendSystemMsg result as null"))?;
+        if !result {
+            return Err(Error::msg("Failed to handle friend request"));
+        }
+        Ok(())
+    }
+}
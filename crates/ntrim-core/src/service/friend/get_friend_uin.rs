@@ -0,0 +1,40 @@
+use std::sync::Arc;
+use log::warn;
+use crate::bot::Bot;
+
+impl Bot {
+    /// 通过uid获取好友的uin
+    pub async fn get_friend_uin(self: &Arc<Self>, uid: &str) -> Option<i64> {
+        if uid.is_empty() {
+            return None;
+        }
+        if uid == self.client.session.read().await.uid {
+            return Some(self.unique_id);
+        }
+        for refresh in [false, true] {
+            match self.get_friend_list(refresh).await {
+                Ok(list) => if let Some(friend) = list.friends.into_iter().find(|f| f.uid == uid) {
+                    return Some(friend.uin);
+                },
+                Err(e) => warn!("Failed to get friend list: {:?}", e)
+            }
+        }
+        None
+    }
+
+    /// 通过uin获取好友的uid
+    pub async fn get_friend_uid(self: &Arc<Self>, uin: i64) -> Option<String> {
+        if uin == self.unique_id {
+            return Some(self.client.session.read().await.uid.clone());
+        }
+        for refresh in [false, true] {
+            match self.get_friend_list(refresh).await {
+                Ok(list) => if let Some(friend) = list.friends.into_iter().find(|f| f.uin == uin) {
+                    return Some(friend.uid);
+                },
+                Err(e) => warn!("Failed to get friend list: {:?}", e)
+            }
+        }
+        None
+    }
+}
\ No newline at end of file
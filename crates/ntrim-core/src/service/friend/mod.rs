@@ -1 +1,3 @@
-mod get_friend_list;
\ No newline at end of file
+mod get_friend_list;
+mod friend_request;
+mod get_friend_uin;
\ No newline at end of file
@@ -3,4 +3,5 @@ pub mod friend;
 pub mod msg;
 pub mod heartbeat;
 mod troop;
-mod bdh;
\ No newline at end of file
+mod bdh;
+mod file;
\ No newline at end of file
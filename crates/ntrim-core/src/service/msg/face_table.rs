@@ -0,0 +1,222 @@
+/// 表情的发送方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FaceKind {
+    /// 旧版表情，使用Face元素
+    Normal,
+    /// 新增的表情，使用CommonElem(33)
+    Extended,
+}
+
+/// 表情的元数据，来自客户端的表情配置
+#[derive(Debug)]
+pub(crate) struct FaceInfo {
+    pub id: u32,
+    pub name: &'static str,
+    pub kind: FaceKind,
+    /// 超级表情(CommonElem(37))的stick_id，没有超级表情时为None
+    pub stick_id: Option<&'static str>,
+}
+
+/// 超级表情都在这个表情包里面
+pub(crate) const SUPER_FACE_PACK_ID: &str = "1";
+
+macro_rules! face {
+    ($id:expr, $name:expr, $kind:ident) => {
+        FaceInfo { id: $id, name: $name, kind: FaceKind::$kind, stick_id: None }
+    };
+    ($id:expr, $name:expr, $kind:ident, $stick_id:expr) => {
+        FaceInfo { id: $id, name: $name, kind: FaceKind::$kind, stick_id: Some($stick_id) }
+    };
+}
+
+/// 按id排序，查找时使用二分
+static FACES: &[FaceInfo] = &[
+    face!(0, "惊讶", Normal),
+    face!(1, "撇嘴", Normal),
+    face!(2, "色", Normal),
+    face!(3, "发呆", Normal),
+    face!(4, "得意", Normal),
+    face!(5, "流泪", Normal, "16"),
+    face!(6, "害羞", Normal),
+    face!(7, "闭嘴", Normal),
+    face!(8, "睡", Normal),
+    face!(9, "大哭", Normal),
+    face!(10, "尴尬", Normal),
+    face!(11, "发怒", Normal),
+    face!(12, "调皮", Normal),
+    face!(13, "呲牙", Normal),
+    face!(14, "微笑", Normal),
+    face!(15, "难过", Normal),
+    face!(16, "酷", Normal),
+    face!(18, "抓狂", Normal),
+    face!(19, "吐", Normal),
+    face!(20, "偷笑", Normal),
+    face!(21, "可爱", Normal),
+    face!(22, "白眼", Normal),
+    face!(23, "傲慢", Normal),
+    face!(24, "饥饿", Normal),
+    face!(25, "困", Normal),
+    face!(26, "惊恐", Normal),
+    face!(27, "流汗", Normal),
+    face!(28, "憨笑", Normal),
+    face!(29, "悠闲", Normal),
+    face!(30, "奋斗", Normal),
+    face!(31, "咒骂", Normal),
+    face!(32, "疑问", Normal),
+    face!(33, "嘘", Normal),
+    face!(34, "晕", Normal),
+    face!(35, "折磨", Normal),
+    face!(36, "衰", Normal),
+    face!(37, "骷髅", Normal),
+    face!(38, "敲打", Normal),
+    face!(39, "再见", Normal),
+    face!(41, "发抖", Normal),
+    face!(42, "爱情", Normal),
+    face!(43, "跳跳", Normal),
+    face!(46, "猪头", Normal),
+    face!(49, "拥抱", Normal),
+    face!(53, "蛋糕", Normal),
+    face!(56, "刀", Normal),
+    face!(59, "便便", Normal),
+    face!(60, "咖啡", Normal),
+    face!(63, "玫瑰", Normal),
+    face!(64, "凋谢", Normal),
+    face!(66, "爱心", Normal),
+    face!(67, "心碎", Normal),
+    face!(74, "太阳", Normal, "35"),
+    face!(75, "月亮", Normal, "36"),
+    face!(76, "赞", Normal),
+    face!(77, "踩", Normal),
+    face!(78, "握手", Normal),
+    face!(79, "胜利", Normal),
+    face!(85, "飞吻", Normal),
+    face!(86, "怄火", Normal),
+    face!(89, "西瓜", Normal),
+    face!(96, "冷汗", Normal),
+    face!(97, "擦汗", Normal),
+    face!(98, "抠鼻", Normal),
+    face!(99, "鼓掌", Normal),
+    face!(100, "糗大了", Normal),
+    face!(101, "坏笑", Normal),
+    face!(102, "左哼哼", Normal),
+    face!(103, "右哼哼", Normal),
+    face!(104, "哈欠", Normal),
+    face!(105, "鄙视", Normal),
+    face!(106, "委屈", Normal),
+    face!(107, "快哭了", Normal),
+    face!(108, "阴险", Normal),
+    face!(109, "左亲亲", Normal),
+    face!(110, "吓", Normal),
+    face!(111, "可怜", Normal),
+    face!(112, "菜刀", Normal),
+    face!(114, "篮球", Normal, "13"),
+    face!(116, "示爱", Normal),
+    face!(118, "抱拳", Normal),
+    face!(119, "勾引", Normal),
+    face!(120, "拳头", Normal),
+    face!(121, "差劲", Normal),
+    face!(123, "NO", Normal),
+    face!(124, "OK", Normal),
+    face!(125, "转圈", Normal),
+    face!(129, "挥手", Normal),
+    face!(144, "喝彩", Normal),
+    face!(147, "棒棒糖", Normal),
+    face!(171, "茶", Normal),
+    face!(173, "泪奔", Normal),
+    face!(174, "无奈", Normal),
+    face!(175, "卖萌", Normal),
+    face!(176, "小纠结", Normal),
+    face!(178, "斜眼笑", Normal),
+    face!(179, "doge", Normal),
+    face!(180, "惊喜", Normal),
+    face!(181, "戳一戳", Normal, "37"),
+    face!(182, "笑哭", Normal),
+    face!(183, "我最美", Normal),
+    face!(201, "点赞", Normal),
+    face!(203, "托脸", Normal),
+    face!(212, "托腮", Normal),
+    face!(214, "啵啵", Normal),
+    face!(219, "蹭一蹭", Normal),
+    face!(222, "抱抱", Normal),
+    face!(227, "拍手", Normal),
+    face!(232, "佛系", Normal),
+    face!(240, "喷脸", Normal),
+    face!(243, "甩头", Normal),
+    face!(246, "加油抱抱", Normal),
+    face!(260, "搬砖中", Extended),
+    face!(261, "忙到飞起", Extended),
+    face!(262, "脑阔疼", Extended),
+    face!(263, "沧桑", Extended),
+    face!(264, "捂脸", Extended),
+    face!(265, "辣眼睛", Extended),
+    face!(266, "哦哟", Extended),
+    face!(267, "头秃", Extended),
+    face!(268, "问号脸", Extended),
+    face!(269, "暗中观察", Extended),
+    face!(270, "emm", Extended),
+    face!(271, "吃瓜", Extended),
+    face!(272, "呵呵哒", Extended),
+    face!(273, "我酸了", Extended),
+    face!(277, "汪汪", Extended),
+    face!(281, "无眼笑", Extended),
+    face!(282, "敬礼", Extended),
+    face!(283, "狂笑", Extended),
+    face!(284, "面无表情", Extended),
+    face!(285, "摸鱼", Extended),
+    face!(286, "魔鬼笑", Extended),
+    face!(287, "哦", Extended),
+    face!(289, "睁眼", Extended),
+    face!(293, "摸锦鲤", Extended),
+    face!(294, "期待", Extended),
+    face!(297, "拜谢", Extended),
+    face!(298, "元宝", Extended),
+    face!(299, "牛啊", Extended),
+    face!(305, "右亲亲", Extended),
+    face!(306, "牛气冲天", Extended),
+    face!(307, "喵喵", Extended),
+    face!(311, "打call", Extended, "1"),
+    face!(312, "变形", Extended, "2"),
+    face!(314, "仔细分析", Extended, "4"),
+    face!(317, "菜汪", Extended, "7"),
+    face!(318, "崇拜", Extended, "8"),
+    face!(319, "比心", Extended, "9"),
+    face!(320, "庆祝", Extended, "10"),
+    face!(323, "嫌弃", Extended),
+    face!(324, "吃糖", Extended, "12"),
+    face!(325, "惊吓", Extended, "14"),
+    face!(326, "生气", Extended),
+    face!(332, "举牌牌", Extended),
+    face!(336, "豹富", Extended),
+    face!(337, "花朵脸", Extended, "22"),
+    face!(338, "我想开了", Extended, "20"),
+    face!(339, "舔屏", Extended, "21"),
+    face!(341, "打招呼", Extended, "24"),
+    face!(342, "酸Q", Extended, "26"),
+    face!(343, "我方了", Extended, "27"),
+    face!(344, "大怨种", Extended, "28"),
+    face!(345, "红包多多", Extended, "29"),
+    face!(346, "你真棒棒", Extended, "25"),
+    face!(349, "坚强", Extended, "32"),
+    face!(350, "贴贴", Extended, "31"),
+    face!(351, "敲敲", Extended, "30"),
+    face!(358, "骰子", Extended, "33"),
+    face!(359, "包剪锤", Extended, "34"),
+];
+
+pub(crate) fn find_face(id: u32) -> Option<&'static FaceInfo> {
+    FACES.binary_search_by_key(&id, |face| face.id).ok().map(|index| &FACES[index])
+}
+
+/// 表情的名字，例如`/流泪`，不在表里的表情使用id
+pub(crate) fn face_name(id: u32) -> String {
+    find_face(id).map_or_else(|| format!("/{}", id), |face| format!("/{}", face.name))
+}
+
+#[test]
+fn test_face_table() {
+    assert!(FACES.windows(2).all(|w| w[0].id < w[1].id));
+    assert_eq!(find_face(358).and_then(|face| face.stick_id), Some("33"));
+    assert_eq!(find_face(14).map(|face| face.kind), Some(FaceKind::Normal));
+    assert_eq!(face_name(5), "/流泪");
+    assert_eq!(face_name(100000), "/100000");
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+use anyhow::{anyhow, Error};
+use log::warn;
+use ntrim_tools::cqp::Node;
+use crate::await_response;
+use crate::bot::Bot;
+use crate::pb::msg::{olpush_routing_head, C2c, ContentHead, Elem, Grp, MessageBody, OlpushRoutingHead, RichText};
+use crate::pb::trpc::olpush::Message;
+use crate::service::msg::SendMsgResult;
+use crate::service::msg::message_id::resolve_msg_seq;
+use crate::service::msg::message_factory::{build_forward_card, convert_cq_to_msg};
+use crate::servlet::olpush::msg::{decode_forward_msg, Contact, MessageRecord};
+use crate::servlet::olpush::msg::source::summary_of;
+
+/// 卡片上最多预览的消息条数
+const MAX_PREVIEW_COUNT: usize = 4;
+
+impl Bot {
+    /// 发送合并转发，返回res_id和发送结果
+    pub async fn send_forward_msg(self: &Arc<Bot>, contact: Contact, nodes: Vec<Node>) -> Result<(String, SendMsgResult), Error> {
+        let (res_id, card) = upload_forward_nodes(self, &contact, nodes).await?;
+        let rich_text = RichText {
+            attr: None,
+            elems: vec![card],
+        };
+        let result = Bot::send_rich_text(self, contact, rich_text).await?;
+        Ok((res_id, result))
+    }
+
+    /// 获取合并转发里面的消息，嵌套的合并转发会解析成forward消息段
+    pub async fn get_forward_msg(self: &Arc<Bot>, res_id: String) -> Result<Vec<MessageRecord>, Error> {
+        Bot::fetch_forward_msg(self, res_id, 0).await
+    }
+
+    /// 长消息也是通过这里拉取的，`depth`为嵌套的层数
+    pub(crate) async fn fetch_forward_msg(self: &Arc<Bot>, res_id: String, depth: u32) -> Result<Vec<MessageRecord>, Error> {
+        let messages = await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = Bot::_recv_long_msg(self, res_id).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Tcp connection exception"))
+            }
+        }, |value: Option<Vec<Message>>| {
+            value.ok_or(anyhow!("Failed to get forward msg: no result"))
+        }, |err| {
+            Err(err)
+        })?;
+        let mut records = Vec::with_capacity(messages.len());
+        for msg in messages {
+            if let Some(record) = decode_forward_msg(self, msg, depth).await {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// 上传合并转发的节点，返回res_id和对应的卡片
+pub(crate) async fn upload_forward_nodes(bot: &Arc<Bot>, contact: &Contact, nodes: Vec<Node>) -> Result<(String, Elem), Error> {
+    if nodes.is_empty() {
+        return Err(anyhow!("Forward message must contain at least one node"));
+    }
+    let mut messages = Vec::with_capacity(nodes.len());
+    let mut previews = Vec::new();
+    for node in nodes {
+        let msg = match node.id {
+            Some(id) => match fetch_node_msg(bot, contact, id).await {
+                Ok(msg) => msg,
+                Err(e) => {
+                    warn!("Failed to find forward node {}: {}", id, e);
+                    continue;
+                }
+            },
+            None => {
+                if previews.len() < MAX_PREVIEW_COUNT {
+                    previews.push(format!("{}: {}", node.nickname, summary_of(&node.content)));
+                }
+                // 节点里面可能还有嵌套的合并转发
+                let rich_text = Box::pin(convert_cq_to_msg(bot, contact, node.content)).await?;
+                build_node_msg(contact, node.user_id, node.nickname, rich_text)
+            }
+        };
+        messages.push(msg);
+    }
+    if messages.is_empty() {
+        return Err(anyhow!("No valid forward node"));
+    }
+    let count = messages.len();
+    let res_id = send_long_msg(bot, contact, messages).await?;
+    let card = build_forward_card(contact, &res_id, previews, count);
+    Ok((res_id, card))
+}
+
+/// 上传到长消息服务，返回res_id
+pub(super) async fn send_long_msg(bot: &Arc<Bot>, contact: &Contact, messages: Vec<Message>) -> Result<String, Error> {
+    let (group_id, uid) = match contact {
+        Contact::Group(_, group_id) => (*group_id, "".to_string()),
+        Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) => (0, uid.clone()),
+    };
+    await_response!(tokio::time::Duration::from_secs(10), async {
+        let rx = Bot::_send_long_msg(bot, group_id, uid, messages).await;
+        if let Some(rx) = rx {
+            rx.await.map_err(|e| Error::new(e))
+        } else {
+            Err(Error::msg("Tcp connection exception"))
+        }
+    }, |value: Option<String>| {
+        value.ok_or(anyhow!("Failed to upload long msg: no res_id"))
+    }, |err| {
+        Err(err)
+    })
+}
+
+pub(super) fn build_node_msg(contact: &Contact, user_id: i64, nickname: String, rich_text: RichText) -> Message {
+    let (msg_type, contact) = match contact {
+        Contact::Group(_, group_id) => (82, olpush_routing_head::Contact::Grp(Grp {
+            group_id: *group_id,
+            sender_nick: Some(nickname),
+            group_name: None,
+        })),
+        _ => (166, olpush_routing_head::Contact::C2c(C2c {
+            friend_name: Some(nickname),
+        })),
+    };
+    let msg_seq = rand::random::<u32>() as i64;
+    Message {
+        routing_head: OlpushRoutingHead {
+            peer_id: user_id,
+            contact: Some(contact),
+            ..Default::default()
+        },
+        content_head: ContentHead {
+            msg_type,
+            sub_type: None,
+            c2c_cmd: None,
+            msg_id: rand::random::<u32>() as i64,
+            msg_seq,
+            msg_time: chrono::Local::now().timestamp(),
+            msg_uid: 0x0100_0000_0000_0000 | msg_seq,
+        },
+        msg_body: MessageBody {
+            rich_text: Some(rich_text),
+            msg_content: None,
+        },
+    }
+}
+
+/// 引用已有消息的节点直接使用原始消息
+async fn fetch_node_msg(bot: &Arc<Bot>, contact: &Contact, id: i64) -> Result<Message, Error> {
+    let msg_seq = resolve_msg_seq(bot, contact, id).await?;
+    fetch_raw_msg(bot, contact, msg_seq).await
+}
+
+async fn fetch_raw_msg(bot: &Arc<Bot>, contact: &Contact, msg_seq: i64) -> Result<Message, Error> {
+    let messages = match contact {
+        Contact::Group(_, group_id) => bot.get_group_raw_msg(*group_id, msg_seq, msg_seq).await?,
+        Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) => bot.get_c2c_raw_msg(uid.clone(), msg_seq, msg_seq).await?,
+    };
+    messages.into_iter()
+        .find(|msg| msg.content_head.msg_seq == msg_seq)
+        .ok_or(anyhow!("Message not found"))
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use anyhow::Error;
+use crate::await_response;
+use crate::bot::Bot;
+use crate::pb::trpc::olpush::Message;
+use crate::servlet::olpush::msg::{decode_history_msg, MessageRecord};
+
+impl Bot {
+    /// 获取群历史消息，返回seq在`from_seq`到`from_seq + count - 1`之间的消息，
+    /// 数据库里面已经存在完整的范围时不会请求服务器
+    pub async fn get_group_msg_history(self: &Arc<Self>, group_id: i64, from_seq: i64, count: i64) -> Result<Vec<MessageRecord>, Error> {
+        if count <= 0 {
+            return Ok(Vec::new());
+        }
+        let end_seq = from_seq + count - 1;
+        #[cfg(feature = "sql")]
+        if let Some(records) = query_local_history(self, "group", group_id, from_seq, end_seq).await {
+            return Ok(records);
+        }
+        let messages = self.get_group_raw_msg(group_id, from_seq, end_seq).await?;
+        Ok(decode_history(self, messages).await)
+    }
+
+    /// 获取好友历史消息，参数同[`Bot::get_group_msg_history`]
+    pub async fn get_friend_msg_history(self: &Arc<Self>, friend_uin: i64, from_seq: i64, count: i64) -> Result<Vec<MessageRecord>, Error> {
+        if count <= 0 {
+            return Ok(Vec::new());
+        }
+        let end_seq = from_seq + count - 1;
+        #[cfg(feature = "sql")]
+        if let Some(records) = query_local_history(self, "friend", friend_uin, from_seq, end_seq).await {
+            return Ok(records);
+        }
+        let friend_uid = self.get_friend_uid(friend_uin).await
+            .ok_or(Error::msg(format!("Unable to find uid of friend {}", friend_uin)))?;
+        let messages = self.get_c2c_raw_msg(friend_uid, from_seq, end_seq).await?;
+        Ok(decode_history(self, messages).await)
+    }
+
+    /// 拉取群消息原始推送，包括`start_seq`和`end_seq`
+    pub(crate) async fn get_group_raw_msg(self: &Arc<Self>, group_id: i64, start_seq: i64, end_seq: i64) -> Result<Vec<Message>, Error> {
+        await_response!(tokio::time::Duration::from_secs(15), async {
+            let rx = Bot::_get_group_msg(self, group_id, start_seq, end_seq).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to get_group_msg: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to get group msg"))
+    }
+
+    /// 拉取好友消息原始推送，包括`start_seq`和`end_seq`
+    pub(crate) async fn get_c2c_raw_msg(self: &Arc<Self>, friend_uid: String, start_seq: i64, end_seq: i64) -> Result<Vec<Message>, Error> {
+        await_response!(tokio::time::Duration::from_secs(15), async {
+            let rx = Bot::_get_c2c_msg(self, friend_uid, start_seq, end_seq).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to get_c2c_msg: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to get c2c msg"))
+    }
+}
+
+#[cfg(feature = "sql")]
+async fn query_local_history(bot: &Arc<Bot>, contact_type: &str, contact_uin: i64, start_seq: i64, end_seq: i64) -> Option<Vec<MessageRecord>> {
+    if !crate::db::is_initialized() {
+        return None;
+    }
+    let pool = crate::db::PG_POOL.get().unwrap();
+    match MessageRecord::get_messages_by_seq(pool, bot, contact_type, contact_uin, start_seq, end_seq).await {
+        Ok(records) if records.len() as i64 == end_seq - start_seq + 1 => Some(records),
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("Failed to query message history from pgsql: {:?}", e);
+            None
+        }
+    }
+}
+
+async fn decode_history(bot: &Arc<Bot>, mut messages: Vec<Message>) -> Vec<MessageRecord> {
+    messages.sort_by_key(|msg| msg.content_head.msg_seq);
+    let mut records = Vec::with_capacity(messages.len());
+    for msg in messages {
+        if let Some(record) = decode_history_msg(bot, msg).await {
+            records.push(record);
+        }
+    }
+    records
+}
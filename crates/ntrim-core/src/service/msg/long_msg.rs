@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use anyhow::Error;
+use ntrim_tools::flate2::compress_deflate;
+use crate::bot::Bot;
+use crate::pb::msg::{elem, Elem, GeneralFlags, RichMsg, RichText};
+use crate::pb::msg::elem::AioElem;
+use crate::service::msg::forward_msg::{build_node_msg, send_long_msg};
+use crate::servlet::olpush::msg::Contact;
+
+/// 长消息卡片上显示的摘要长度
+const MAX_BRIEF_LEN: usize = 30;
+
+/// 把过长的消息上传到长消息服务，返回引用这条长消息的RichText
+pub(super) async fn upload_long_msg(bot: &Arc<Bot>, contact: &Contact, rich_text: RichText) -> Result<RichText, Error> {
+    let brief = rich_text.elems.iter()
+        .filter_map(|elem| match &elem.aio_elem {
+            Some(AioElem::Text(text)) => Some(text.text.as_str()),
+            _ => None
+        })
+        .collect::<String>()
+        .chars()
+        .take(MAX_BRIEF_LEN)
+        .collect::<String>();
+    let msg = build_node_msg(contact, bot.unique_id, bot.unique_id.to_string(), rich_text);
+    let res_id = send_long_msg(bot, contact, vec![msg]).await?;
+
+    let xml = format!(
+        "<?xml version='1.0' encoding='UTF-8' standalone='yes' ?>\
+        <msg serviceID=\"35\" templateID=\"1\" action=\"viewMultiMsg\" brief=\"{brief}\" m_resid=\"{res_id}\" \
+        m_fileName=\"{file_name}\" sourceMsgId=\"0\" url=\"\" flag=\"3\" adverSign=\"0\" multiMsgFlag=\"1\">\
+        <item layout=\"1\"><title>{brief}</title><hr hidden=\"false\" style=\"0\" /><summary>点击查看完整消息</summary></item>\
+        <source name=\"聊天记录\" icon=\"\" action=\"\" appid=\"-1\" /></msg>",
+        brief = escape_xml(&brief),
+        res_id = escape_xml(&res_id),
+        file_name = chrono::Local::now().timestamp_millis(),
+    );
+    let mut template = vec![1u8];
+    template.extend(compress_deflate(xml.as_bytes()));
+    Ok(RichText {
+        attr: None,
+        elems: vec![
+            Elem {
+                aio_elem: Some(elem::AioElem::GeneralFlags(GeneralFlags {
+                    long_text_flag: Some(1),
+                    long_text_resid: Some(res_id),
+                    pendant_id: Some(0),
+                    ..Default::default()
+                }))
+            },
+            Elem {
+                aio_elem: Some(elem::AioElem::RichMsg(RichMsg {
+                    template1: Some(template),
+                    service_id: Some(35),
+                }))
+            },
+        ],
+    })
+}
+
+pub(super) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -4,19 +4,35 @@ use anyhow::{anyhow, Error};
 use bytes::BufMut;
 use log::warn;
 use prost::Message as _;
-use ntrim_tools::cqp::CQCode;
+use rand::Rng;
+use ntrim_tools::audio::encode_to_silk;
+use ntrim_tools::cqp::{CQCode, Location, MFace, Share};
+use ntrim_tools::flate2::compress_deflate;
 use crate::bot::Bot;
 use crate::Contact;
 use crate::pb::msg::{ * };
 use crate::pb::msg::common_elem::{CommonBigFaceElem, CommonFaceElem};
 use crate::pb::msg::text::TextReversed;
+use crate::service::msg::forward_msg::upload_forward_nodes;
+use crate::service::msg::face_table::{face_name, find_face, FaceKind, SUPER_FACE_PACK_ID};
+use crate::service::msg::long_msg::escape_xml;
+use crate::service::msg::message_id::resolve_msg_seq;
+use crate::service::rich_media::PicUploadOptions;
+use crate::service::rich_media::request_upload_ptt::MAX_PTT_SIZE;
+use crate::service::rich_media::request_upload_video::MAX_VIDEO_SIZE;
+use crate::service::rich_media::resource::{fetch_resource, MAX_PIC_SIZE};
+use crate::servlet::olpush::msg::decoder::{BASKETBALL_FACE_ID, DICE_FACE_ID, RPS_FACE_ID};
+use crate::servlet::olpush::msg::source::find_msg_source;
 
-pub(crate) async fn convert_cq_to_msg(bot: &Arc<Bot>, contact: &Contact, cqs: Vec<CQCode>) -> RichText {
+/// 合并转发上传失败时返回错误，不然会发出一张打不开的卡片
+pub(crate) async fn convert_cq_to_msg(bot: &Arc<Bot>, contact: &Contact, cqs: Vec<CQCode>) -> Result<RichText, Error> {
     let mut elems = vec![
         Elem {
             aio_elem: Some(elem::AioElem::GeneralFlags(
                 GeneralFlags {
                     bubble_diy_text_id: Some(0),
+                    long_text_flag: None,
+                    long_text_resid: None,
                     bubble_sub_id: Some(0),
                     pendant_id: Some(0),
                     pb_reverse: Some(general_flags::PbReverse {
@@ -48,7 +64,37 @@ pub(crate) async fn convert_cq_to_msg(bot: &Arc<Bot>, contact: &Contact, cqs: Ve
         },
     ];
 
+    // 消息里面的node会合并成一条合并转发
+    let (nodes, cqs): (Vec<_>, Vec<_>) = cqs.into_iter()
+        .partition(|cq| matches!(cq, CQCode::Node(_)));
+    if !nodes.is_empty() {
+        let nodes = nodes.into_iter().filter_map(|cq| match cq {
+            CQCode::Node(node) => Some(node),
+            _ => None
+        }).collect();
+        let (_, card) = upload_forward_nodes(bot, contact, nodes).await?;
+        elems.push(card);
+    }
+
     for cq in cqs {
+        if let CQCode::MFace(mface) = cq {
+            match convert_mface_to_elems(mface) {
+                Ok(mface) => elems.extend(mface),
+                Err(e) => {
+                    warn!("Failed to convert MFace to Elem: {}", e);
+                }
+            }
+            continue;
+        }
+        if let CQCode::Reply(reply) = cq {
+            match convert_reply_to_elems(bot, contact, reply.id).await {
+                Ok(reply) => elems.extend(reply),
+                Err(e) => {
+                    warn!("Failed to convert Reply to Elem: {}", e);
+                }
+            }
+            continue;
+        }
         match convert_cq_to_elem(bot, contact, cq).await {
             Ok(elem) => elems.push(elem),
             Err(e) => {
@@ -57,10 +103,10 @@ pub(crate) async fn convert_cq_to_msg(bot: &Arc<Bot>, contact: &Contact, cqs: Ve
         }
     }
 
-    RichText {
+    Ok(RichText {
         attr: None,
         elems
-    }
+    })
 }
 
 async fn convert_cq_to_elem(bot: &Arc<Bot>, contact: &Contact, cq: CQCode) -> Result<Elem, Error> {
@@ -115,33 +161,18 @@ async fn convert_cq_to_elem(bot: &Arc<Bot>, contact: &Contact, cq: CQCode) -> Re
             }
         }
         CQCode::Face(face) => {
+            // 表里没有的表情按id判断，260以后的都是新增的表情
+            let extended = find_face(face.id).map_or(face.id >= 260, |info| info.kind == FaceKind::Extended);
             let elem = if face.big {
-                elem::AioElem::CommonElem(
-                    CommonElem {
-                        service_type: 37,
-                        data: CommonBigFaceElem {
-                            pack_id: Some("1".to_string()),
-                            stick_id: Some("1".to_string()),
-                            face_id: face.id,
-                            flag4: Some(1),
-                            flag5: Some(1),
-                            flag9: Some(1),
-                            face_name: Some(format!("/{}", face.id)),
-                            result: Some("".to_string()),
-                            ..Default::default()
-                        }.encode_to_vec(),
-                        business_type: Some(1)
-                    }
-                )
-            } else if face.id >= 260 {
+                build_big_face(face.id, face.result)?
+            } else if extended {
                 elem::AioElem::CommonElem(
                     CommonElem {
                         service_type: 33,
                         data: CommonFaceElem {
                             face_id: face.id,
-                            face_desc: Some(format!("/{}", face.id)),
-                            face_name: Some(format!("/{}", face.id)),
-                            ..Default::default()
+                            face_desc: Some(face_name(face.id)),
+                            face_name: Some(face_name(face.id)),
                         }.encode_to_vec(),
                         business_type: Some(1)
                     }
@@ -157,47 +188,363 @@ async fn convert_cq_to_elem(bot: &Arc<Bot>, contact: &Contact, cq: CQCode) -> Re
                 aio_elem: Some(elem)
             }
         }
-        CQCode::Image(ref image) => {
-            // linux: /mnt/1234567890.jpg
-            // linux: ../1234567890.jpg !dangerous!
-            // windows: C:\Users\1234567890.jpg
-            let elem = if std::path::Path::new(&image.file).exists() {
-                let msg_info = Bot::upload_group_pic(bot, 645830205, image.file.clone(), true).await?;
-                elem::AioElem::CommonElem(
+        CQCode::Image(image) => {
+            let src = if image.file.is_empty() { &image.url } else { &image.file };
+            let pic_bytes = fetch_resource(src, MAX_PIC_SIZE).await?;
+            let summary = if image.r#type == "flash" {
+                // NT协议已经没有闪照了，只能当普通图片发
+                warn!("Flash image is not supported, send as normal image");
+                if image.summary.is_empty() { "[闪照]".to_string() } else { image.summary }
+            } else {
+                image.summary
+            };
+            let options = PicUploadOptions {
+                // type里面的show是秀图特效，和是否原图无关，总是按原图发送
+                original: true,
+                sub_type: image.sub_type,
+                summary,
+            };
+            let msg_info = bot.upload_pic(contact, pic_bytes, options).await?;
+            Elem {
+                aio_elem: Some(elem::AioElem::CommonElem(
                     CommonElem {
                         service_type: 48,
                         data: msg_info.encode_to_vec(),
-                        business_type: Some(20)
+                        business_type: Some(match contact {
+                            Contact::Group(..) => 20,
+                            _ => 10,
+                        })
                     }
-                )
-            } else {
-                return Err(anyhow!("Unsupported Image CQCode: {}", cq.to_string()))
+                ))
+            }
+        }
+        CQCode::Record(record) => {
+            let src = if record.file.is_empty() { record.url.as_deref().unwrap_or_default() } else { &record.file };
+            let data = fetch_resource(src, MAX_PTT_SIZE).await?;
+            let silk = tokio::task::spawn_blocking(move || encode_to_silk(&data)).await??;
+            let msg_info = bot.upload_ptt(contact, silk).await?;
+            Elem {
+                aio_elem: Some(elem::AioElem::CommonElem(
+                    CommonElem {
+                        service_type: 48,
+                        data: msg_info.encode_to_vec(),
+                        business_type: Some(match contact {
+                            Contact::Group(..) => 22,
+                            _ => 12,
+                        })
+                    }
+                ))
+            }
+        }
+        CQCode::Video(video) => {
+            let src = if video.file.is_empty() { video.url.as_deref().unwrap_or_default() } else { &video.file };
+            let data = fetch_resource(src, MAX_VIDEO_SIZE).await?;
+            let thumb = match &video.cover {
+                Some(cover) => Some(fetch_resource(cover, MAX_PIC_SIZE).await?),
+                None => None
             };
+            let msg_info = bot.upload_video(contact, data, thumb).await?;
             Elem {
-                aio_elem: Some(elem)
+                aio_elem: Some(elem::AioElem::CommonElem(
+                    CommonElem {
+                        service_type: 48,
+                        data: msg_info.encode_to_vec(),
+                        business_type: Some(match contact {
+                            Contact::Group(..) => 21,
+                            _ => 11,
+                        })
+                    }
+                ))
             }
         }
+        CQCode::NewDice(dice) => Elem {
+            aio_elem: Some(build_big_face(DICE_FACE_ID, magic_face_result(dice.id, 6)?)?)
+        },
+        CQCode::NewRPS(rps) => Elem {
+            aio_elem: Some(build_big_face(RPS_FACE_ID, magic_face_result(rps.id, 3)?)?)
+        },
+        CQCode::Basketball(basketball) => Elem {
+            aio_elem: Some(build_big_face(BASKETBALL_FACE_ID, magic_face_result(basketball.id, 5)?)?)
+        },
+        CQCode::Forward(forward) => build_forward_card(contact, &forward.id, Vec::new(), 0),
+        CQCode::Json(json) => build_light_app(&json.data),
+        CQCode::Xml(xml) => build_rich_msg(&xml.data, xml.service_id),
+        CQCode::Share(share) => build_rich_msg(&build_share_xml(&share), 1),
+        CQCode::Music(music) => {
+            let (url, audio) = match music.music_type.as_str() {
+                "qq" => (format!("https://i.y.qq.com/v8/playsong.html?songid={}", music.id), "".to_string()),
+                "163" => (
+                    format!("https://music.163.com/song/{}/", music.id),
+                    format!("https://music.163.com/song/media/outer/url?id={}.mp3", music.id)
+                ),
+                _ => return Err(anyhow!("Unsupported music type: {}", music.music_type))
+            };
+            build_rich_msg(&build_music_xml(&music.music_type, &url, &audio, "分享歌曲", "", None), 2)
+        }
+        CQCode::CustomMusic(music) => build_rich_msg(&build_music_xml(
+            &music.music_type, &music.url, &music.audio, &music.title, &music.singer, music.image.as_deref()
+        ), 2),
+        CQCode::Location(location) => build_light_app(&build_location_json(&location).to_string()),
         _ => return Err(anyhow!("Unsupported CQCode: {}", cq.to_string()))
 /*
-        CQCode::Image(_) => {}
         CQCode::BubbleFace(_) => {}
-        CQCode::Reply(_) => {}
-        CQCode::Record(_) => {}
-        CQCode::Video(_) => {}
-        CQCode::Basketball(_) => {}
-        CQCode::NewRPS(_) => {}
-        CQCode::NewDice(_) => {}
         CQCode::Poke(_) => {}
         CQCode::Touch(_) => {}
-        CQCode::Music(_) => {}
         CQCode::Weather(_) => {}
-        CQCode::Location(_) => {}
-        CQCode::Share(_) => {}
-        CQCode::Gift(_) => {}
-        CQCode::CustomMusic(_) => {}*/
+        CQCode::Gift(_) => {}*/
     })
 }
 
+/// 大表情(超级表情)，骰子、猜拳这类魔法表情的结果也放在这里，表里没有超级表情的返回错误
+fn build_big_face(face_id: u32, result: u32) -> Result<elem::AioElem, Error> {
+    let stick_id = find_face(face_id)
+        .and_then(|face| face.stick_id)
+        .ok_or(anyhow!("Face {} has no super face", face_id))?;
+    Ok(elem::AioElem::CommonElem(
+        CommonElem {
+            service_type: 37,
+            data: CommonBigFaceElem {
+                pack_id: Some(SUPER_FACE_PACK_ID.to_string()),
+                stick_id: Some(stick_id.to_string()),
+                face_id,
+                flag4: Some(1),
+                flag5: Some(1),
+                flag9: Some(1),
+                face_name: Some(face_name(face_id)),
+                result: Some(if result == 0 { "".to_string() } else { result.to_string() }),
+            }.encode_to_vec(),
+            business_type: Some(1)
+        }
+    ))
+}
+
+/// 魔法表情的结果，为0时随机，超出`1..=max`时返回错误
+fn magic_face_result(id: i32, max: u32) -> Result<u32, Error> {
+    match id {
+        0 => Ok(rand::thread_rng().gen_range(1..=max)),
+        id if id >= 1 && id as u32 <= max => Ok(id as u32),
+        id => Err(anyhow!("Invalid magic face result: {}, expected 1..={}", id, max)),
+    }
+}
+
+/// 合并转发的卡片，`count`为0时不显示条数
+pub(crate) fn build_forward_card(contact: &Contact, res_id: &str, previews: Vec<String>, count: usize) -> Elem {
+    let uuid = uuid::Uuid::new_v4().to_string();
+    let source = match contact {
+        Contact::Group(..) => "群聊的聊天记录",
+        _ => "聊天记录",
+    };
+    let summary = if count == 0 {
+        "查看转发消息".to_string()
+    } else {
+        format!("查看{}条转发消息", count)
+    };
+    let json = serde_json::json!({
+        "app": "com.tencent.multimsg",
+        "config": {
+            "autosize": 1,
+            "forward": 1,
+            "round": 1,
+            "type": "normal",
+            "width": 300
+        },
+        "desc": "[聊天记录]",
+        "extra": serde_json::json!({
+            "filename": uuid,
+            "tsum": count
+        }).to_string(),
+        "meta": {
+            "detail": {
+                "news": previews.into_iter().map(|text| serde_json::json!({ "text": text })).collect::<Vec<_>>(),
+                "resid": res_id,
+                "source": source,
+                "summary": summary,
+                "uniseq": uuid
+            }
+        },
+        "prompt": "[聊天记录]",
+        "ver": "0.0.0.5",
+        "view": "contact"
+    });
+    build_light_app(&json.to_string())
+}
+
+/// 小程序卡片，内容为[1] + zlib压缩后的json
+fn build_light_app(json: &str) -> Elem {
+    let mut data = vec![1u8];
+    data.extend(compress_deflate(json.as_bytes()));
+    Elem {
+        aio_elem: Some(elem::AioElem::ArkJson(LightArk {
+            data
+        }))
+    }
+}
+
+/// xml卡片，内容为[1] + zlib压缩后的xml
+fn build_rich_msg(xml: &str, service_id: i32) -> Elem {
+    let mut template = vec![1u8];
+    template.extend(compress_deflate(xml.as_bytes()));
+    Elem {
+        aio_elem: Some(elem::AioElem::RichMsg(RichMsg {
+            template1: Some(template),
+            service_id: Some(service_id),
+        }))
+    }
+}
+
+/// 链接分享，serviceID为1
+fn build_share_xml(share: &Share) -> String {
+    let title = share.title.as_deref().unwrap_or(&share.url);
+    format!(
+        "<?xml version='1.0' encoding='UTF-8' standalone='yes' ?>\
+        <msg serviceID=\"1\" templateID=\"1\" action=\"web\" brief=\"[分享] {title}\" sourceMsgId=\"0\" url=\"{url}\" flag=\"0\" adverSign=\"0\" multiMsgFlag=\"0\">\
+        <item layout=\"2\"><picture cover=\"{image}\" w=\"0\" h=\"0\" /><title>{title}</title><summary>{content}</summary></item>\
+        <source name=\"\" icon=\"\" action=\"\" appid=\"-1\" /></msg>",
+        title = escape_xml(title),
+        url = escape_xml(&share.url),
+        image = escape_xml(share.image.as_deref().unwrap_or_default()),
+        content = escape_xml(share.content.as_deref().unwrap_or_default()),
+    )
+}
+
+/// 音乐分享，serviceID为2
+fn build_music_xml(music_type: &str, url: &str, audio: &str, title: &str, singer: &str, image: Option<&str>) -> String {
+    let (source, app_id) = match music_type {
+        "qq" => ("QQ音乐", 100497308),
+        "163" => ("网易云音乐", 100495085),
+        _ => ("", -1),
+    };
+    format!(
+        "<?xml version='1.0' encoding='UTF-8' standalone='yes' ?>\
+        <msg serviceID=\"2\" templateID=\"1\" action=\"web\" brief=\"[分享] {title}\" sourceMsgId=\"0\" url=\"{url}\" flag=\"0\" adverSign=\"0\" multiMsgFlag=\"0\">\
+        <item layout=\"2\"><audio cover=\"{image}\" src=\"{audio}\" /><title>{title}</title><summary>{singer}</summary></item>\
+        <source name=\"{source}\" icon=\"\" action=\"\" appid=\"{app_id}\" /></msg>",
+        title = escape_xml(title),
+        url = escape_xml(url),
+        image = escape_xml(image.unwrap_or_default()),
+        audio = escape_xml(audio),
+        singer = escape_xml(singer),
+    )
+}
+
+fn build_location_json(location: &Location) -> serde_json::Value {
+    let name = location.title.as_deref().unwrap_or("位置分享");
+    serde_json::json!({
+        "app": "com.tencent.map",
+        "config": {
+            "autosize": 1,
+            "forward": 1,
+            "type": "normal"
+        },
+        "desc": "地图",
+        "meta": {
+            "Location.Search": {
+                "address": location.content.as_deref().unwrap_or_default(),
+                "from": "plusPanel",
+                "id": "",
+                "lat": location.lat.to_string(),
+                "lng": location.lon.to_string(),
+                "name": name
+            }
+        },
+        "prompt": format!("[位置]{}", name),
+        "ver": "1.1.2.21",
+        "view": "LocationShare"
+    })
+}
+
+/// 商城表情后面需要跟一个表情名的文本，不支持商城表情的客户端会显示这个文本
+fn convert_mface_to_elems(mface: MFace) -> Result<Vec<Elem>, Error> {
+    let face_id = hex::decode(&mface.emoji_id)
+        .map_err(|_| anyhow!("Invalid emoji_id: {}", mface.emoji_id))?;
+    Ok(vec![
+        Elem {
+            aio_elem: Some(elem::AioElem::MarketFace(MarketFace {
+                face_name: Some(mface.summary.clone().into_bytes()),
+                item_type: Some(6),
+                face_info: Some(1),
+                face_id: Some(face_id),
+                tab_id: Some(mface.emoji_package_id),
+                sub_type: Some(3),
+                key: Some(mface.key.into_bytes()),
+                media_type: Some(0),
+                image_width: Some(300),
+                image_height: Some(300),
+                // 1: {1: 300, 2: 300}, 8: 1
+                pb_reserve: Some(vec![0x0a, 0x06, 0x08, 0xac, 0x02, 0x10, 0xac, 0x02, 0x40, 0x01]),
+                ..Default::default()
+            }))
+        },
+        Elem {
+            aio_elem: Some(elem::AioElem::Text(Text {
+                text: mface.summary,
+                ..Default::default()
+            }))
+        },
+    ])
+}
+
+/// 回复消息由原消息的SrcMsg和一个兼容旧版本客户端的艾特组成
+async fn convert_reply_to_elems(bot: &Arc<Bot>, contact: &Contact, id: i64) -> Result<Vec<Elem>, Error> {
+    let msg_seq = resolve_msg_seq(bot, contact, id).await?;
+    let source = find_msg_source(bot, contact, msg_seq).await
+        .ok_or(anyhow!("Unable to find source message: {}", msg_seq))?;
+    let receiver_uid = match contact {
+        Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) => Some(uid.clone()),
+        Contact::Group(..) => None,
+    };
+    let mut elems = vec![Elem {
+        aio_elem: Some(elem::AioElem::SrcMsg(SrcMsg {
+            orginal_seqs: vec![source.msg_seq],
+            sender_uin: Some(source.sender_id),
+            time: Some(source.msg_time),
+            flag: Some(1),
+            elems: vec![Elem {
+                aio_elem: Some(elem::AioElem::Text(Text {
+                    text: source.summary,
+                    ..Default::default()
+                }))
+            }],
+            r#type: Some(0),
+            pb_reverse: Some(src_msg::PbReverse {
+                msg_uid: Some(source.msg_uid),
+                sender_uid: Some(source.sender_uid),
+                receiver_uid,
+            }),
+            to_uin: Some(0),
+        }))
+    }];
+    if let Contact::Group(_, group_id) = contact {
+        let nick = if source.sender_nick.is_empty() {
+            get_group_member_info(bot, *group_id, source.sender_id).await
+                .map_or_else(|_| source.sender_id.to_string(), |(nick, _)| nick)
+        } else {
+            source.sender_nick
+        };
+        let mut attr6 = Vec::new();
+        attr6.put_u16(1);
+        attr6.put_u16(0);
+        attr6.put_u16(nick.len() as u16 + 1);
+        attr6.put_u8(0);
+        attr6.put_u32(source.sender_id as u32);
+        attr6.put_u16(0);
+        elems.push(Elem {
+            aio_elem: Some(elem::AioElem::Text(Text {
+                text: format!("@{}", nick),
+                attr_6: Some(attr6),
+                ..Default::default()
+            }))
+        });
+        elems.push(Elem {
+            aio_elem: Some(elem::AioElem::Text(Text {
+                text: " ".to_string(),
+                ..Default::default()
+            }))
+        });
+    }
+    Ok(elems)
+}
+
 async fn get_group_member_info(bot: &Arc<Bot>, group_id: i64, user_id: i64) -> anyhow::Result<(String, String)> {
     if user_id == 0 {
         Ok(("全体成员".to_string(), "0".to_string()))
@@ -0,0 +1,228 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use anyhow::{anyhow, Error};
+use once_cell::sync::Lazy;
+use crate::bot::Bot;
+use crate::servlet::olpush::msg::{Contact, MessageRecord};
+
+/// 内存中最多保存的消息id数量
+const MAX_CACHED_ID: usize = 65536;
+
+/// OneBot使用的32位消息id，优先使用(bot, 会话, msg_seq)的哈希，冲突时顺延
+#[derive(Debug, Clone)]
+pub struct MessageId {
+    pub id: i32,
+    pub contact: Contact,
+    pub msg_seq: i64,
+    /// 自己发出去的私聊消息撤回时要用，其他消息为0
+    pub client_seq: i64,
+    pub msg_uid: i64,
+    pub msg_time: i64,
+}
+
+impl MessageId {
+    /// 这里的id只是候选值，保存的时候冲突了会换一个，以`Bot::save_message_id`返回的为准
+    pub fn new(bot_uin: i64, contact: Contact, msg_seq: i64, msg_uid: i64, msg_time: i64) -> Self {
+        let (contact_type, contact_uin) = contact_key(&contact);
+        let digest = md5::compute(format!("{}:{}:{}:{}", bot_uin, contact_type, contact_uin, msg_seq));
+        let id = i32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) & i32::MAX;
+        Self {
+            id,
+            contact,
+            msg_seq,
+            client_seq: 0,
+            msg_uid,
+            msg_time,
+        }
+    }
+
+    pub fn from_record(bot_uin: i64, record: &MessageRecord) -> Self {
+        Self::new(bot_uin, record.contact.clone(), record.msg_seq, record.msg_uid, record.msg_time)
+    }
+
+    /// 会话和msg_seq相同就是同一条消息
+    pub fn is_same_msg(&self, other: &MessageId) -> bool {
+        self.msg_seq == other.msg_seq && contact_key(&self.contact) == contact_key(&other.contact)
+    }
+
+    /// 只知道msg_seq的时候从内存里找，找不到返回None
+    pub fn find_by_seq(bot_uin: i64, contact: &Contact, msg_seq: i64) -> Option<MessageId> {
+        let cache = MESSAGE_ID_CACHE.lock().unwrap();
+        let id = cache.seqs.get(&seq_key(bot_uin, contact, msg_seq))?;
+        cache.ids.get(&(bot_uin, *id)).cloned()
+    }
+}
+
+pub(crate) fn contact_key(contact: &Contact) -> (&'static str, i64) {
+    match contact {
+        Contact::Group(_, uin) => ("group", *uin),
+        Contact::Friend(_, uin, _) => ("friend", *uin),
+        Contact::Stranger(_, uin, _) => ("stranger", *uin),
+    }
+}
+
+//             bot_uin contact_type contact_uin msg_seq
+type SeqKey = (i64, &'static str, i64, i64);
+
+fn seq_key(bot_uin: i64, contact: &Contact, msg_seq: i64) -> SeqKey {
+    let (contact_type, contact_uin) = contact_key(contact);
+    (bot_uin, contact_type, contact_uin, msg_seq)
+}
+
+#[derive(Default)]
+struct MessageIdCache {
+    //       bot_uin id
+    ids: HashMap<(i64, i32), MessageId>,
+    seqs: HashMap<SeqKey, i32>,
+    queue: VecDeque<(i64, i32)>,
+}
+
+static MESSAGE_ID_CACHE: Lazy<Mutex<MessageIdCache>> = Lazy::new(|| Mutex::new(MessageIdCache::default()));
+
+/// 缓存消息id，返回这条消息实际使用的id，id已经被别的消息占用时返回None
+fn cache_message_id(bot_uin: i64, message_id: MessageId) -> Option<MessageId> {
+    let mut guard = MESSAGE_ID_CACHE.lock().unwrap();
+    let cache = &mut *guard;
+    let key = seq_key(bot_uin, &message_id.contact, message_id.msg_seq);
+    if let Some(saved) = cache.seqs.get(&key).and_then(|id| cache.ids.get_mut(&(bot_uin, *id))) {
+        if saved.client_seq == 0 {
+            saved.client_seq = message_id.client_seq;
+        }
+        return Some(saved.clone());
+    }
+    let id = (bot_uin, message_id.id);
+    if cache.ids.contains_key(&id) {
+        return None;
+    }
+    cache.seqs.insert(key, message_id.id);
+    cache.ids.insert(id, message_id.clone());
+    cache.queue.push_back(id);
+    if cache.queue.len() > MAX_CACHED_ID {
+        if let Some((old_bot, old_id)) = cache.queue.pop_front() {
+            if let Some(old) = cache.ids.remove(&(old_bot, old_id)) {
+                cache.seqs.remove(&seq_key(old_bot, &old.contact, old.msg_seq));
+            }
+        }
+    }
+    Some(message_id)
+}
+
+#[inline]
+fn next_candidate(id: i32) -> i32 {
+    id.wrapping_add(1) & i32::MAX
+}
+
+impl Bot {
+    /// 记录消息id，开启sql时同时保存到数据库，返回这条消息实际使用的id
+    ///
+    /// 同一条消息重复保存得到的是同一个id，候选id被别的消息占用时往后顺延
+    pub async fn save_message_id(self: &Arc<Bot>, mut message_id: MessageId) -> MessageId {
+        if let Some(saved) = self.find_saved_message_id(&message_id).await {
+            // 推送回来的消息可能比发送结果先保存，这时候还没有client_seq
+            if saved.client_seq == 0 && message_id.client_seq != 0 {
+                #[cfg(feature = "sql")]
+                if crate::db::is_initialized() {
+                    let pool = crate::db::PG_POOL.get().unwrap();
+                    if let Err(e) = MessageId::set_client_seq(pool, self, saved.id, message_id.client_seq).await {
+                        log::warn!("Failed to update message id in pgsql: {:?}", e);
+                    }
+                }
+                return cache_message_id(self.unique_id, MessageId { id: saved.id, ..message_id }).unwrap_or(saved);
+            }
+            return saved;
+        }
+        loop {
+            #[cfg(feature = "sql")]
+            if crate::db::is_initialized() {
+                let pool = crate::db::PG_POOL.get().unwrap();
+                match MessageId::insert(pool, self, &message_id).await {
+                    Ok(true) => {}
+                    // 同一条消息可能同时被保存，比如发送结果和推送回来的消息
+                    Ok(false) => match MessageId::get_by_id(pool, self, message_id.id).await {
+                        Ok(Some(saved)) if saved.is_same_msg(&message_id) => {
+                            return cache_message_id(self.unique_id, saved.clone()).unwrap_or(saved);
+                        }
+                        Ok(_) => {
+                            message_id.id = next_candidate(message_id.id);
+                            continue;
+                        }
+                        Err(e) => log::warn!("Failed to query message id from pgsql: {:?}", e),
+                    },
+                    Err(e) => log::warn!("Failed to insert message id to pgsql: {:?}", e),
+                }
+            }
+            match cache_message_id(self.unique_id, message_id.clone()) {
+                Some(saved) => return saved,
+                None => message_id.id = next_candidate(message_id.id),
+            }
+        }
+    }
+
+    /// 这条消息之前保存过的id，先查内存再查数据库
+    async fn find_saved_message_id(self: &Arc<Bot>, message_id: &MessageId) -> Option<MessageId> {
+        if let Some(saved) = MessageId::find_by_seq(self.unique_id, &message_id.contact, message_id.msg_seq) {
+            return Some(saved);
+        }
+        #[cfg(feature = "sql")]
+        if crate::db::is_initialized() {
+            let pool = crate::db::PG_POOL.get().unwrap();
+            match MessageId::get_by_seq(pool, self, &message_id.contact, message_id.msg_seq).await {
+                Ok(Some(saved)) => return Some(cache_message_id(self.unique_id, saved.clone()).unwrap_or(saved)),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to query message id from pgsql: {:?}", e),
+            }
+        }
+        None
+    }
+
+    /// 通过消息id找到对应的消息，先查内存再查数据库
+    pub async fn get_message_id(self: &Arc<Bot>, id: i32) -> Option<MessageId> {
+        if let Some(message_id) = MESSAGE_ID_CACHE.lock().unwrap().ids.get(&(self.unique_id, id)) {
+            return Some(message_id.clone());
+        }
+        #[cfg(feature = "sql")]
+        if crate::db::is_initialized() {
+            let pool = crate::db::PG_POOL.get().unwrap();
+            match MessageId::get_by_id(pool, self, id).await {
+                Ok(Some(message_id)) => {
+                    cache_message_id(self.unique_id, message_id.clone());
+                    return Some(message_id);
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to query message id from pgsql: {:?}", e),
+            }
+        }
+        None
+    }
+}
+
+/// 回复、合并转发里面的消息id转换成msg_seq，找不到或者不是这个会话的消息时返回错误
+pub(crate) async fn resolve_msg_seq(bot: &Arc<Bot>, contact: &Contact, id: i64) -> Result<i64, Error> {
+    let message_id = match i32::try_from(id) {
+        Ok(id) => bot.get_message_id(id).await,
+        Err(_) => None,
+    }.ok_or(anyhow!("Unknown message id: {}", id))?;
+    if contact_key(&message_id.contact) != contact_key(contact) {
+        return Err(anyhow!("Message {} does not belong to this contact", id));
+    }
+    Ok(message_id.msg_seq)
+}
+
+#[test]
+fn test_message_id() {
+    let contact = Contact::Group("".to_string(), 10001);
+    let a = MessageId::new(114514, contact.clone(), 100, 0x0100_0000_1234_5678, 0);
+    let b = MessageId::new(114514, Contact::Group("群名".to_string(), 10001), 100, 0x0100_0000_1234_5678, 1);
+    let c = MessageId::new(114514, contact.clone(), 101, 0x0100_0000_1234_5678, 0);
+    assert_eq!(a.id, b.id);
+    assert_ne!(a.id, c.id);
+    assert!(a.id >= 0);
+
+    assert_eq!(cache_message_id(114514, a.clone()).map(|v| v.id), Some(a.id));
+    assert_eq!(MessageId::find_by_seq(114514, &contact, 100).map(|v| v.id), Some(a.id));
+    assert!(MessageId::find_by_seq(1919810, &contact, 100).is_none());
+    // 同一条消息再次缓存得到原来的id，不同的消息占用同一个id时需要顺延
+    assert_eq!(cache_message_id(114514, MessageId { id: 1, ..b }).map(|v| v.id), Some(a.id));
+    assert!(cache_message_id(114514, MessageId { id: a.id, ..c.clone() }).is_none());
+    assert_eq!(cache_message_id(114514, MessageId { id: next_candidate(a.id), ..c }).map(|v| v.id), Some(next_candidate(a.id)));
+}
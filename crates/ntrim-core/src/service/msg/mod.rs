@@ -1,2 +1,13 @@
 mod send_msg;
-mod message_factory;
\ No newline at end of file
+mod message_factory;
+mod send_poke;
+mod get_msg_history;
+mod forward_msg;
+mod long_msg;
+mod recall_msg;
+mod msg_reaction;
+pub(crate) mod face_table;
+pub(crate) mod message_id;
+
+pub use send_msg::SendMsgResult;
+pub use message_id::MessageId;
\ No newline at end of file
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+use anyhow::Error;
+use crate::await_response;
+use crate::bot::Bot;
+
+impl Bot {
+    /// 给群消息添加或者取消表情回应，`emoji_id`为系统表情id或者emoji的unicode码点
+    pub async fn set_msg_emoji_like(self: &Arc<Self>, group_id: i64, msg_seq: i64, emoji_id: String, add: bool) -> Result<(), Error> {
+        let r#type = reaction_type(&emoji_id);
+        await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = if add {
+                Bot::_add_group_msg_reaction(self, group_id, msg_seq, emoji_id, r#type).await
+            } else {
+                Bot::_del_group_msg_reaction(self, group_id, msg_seq, emoji_id, r#type).await
+            };
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to set_msg_emoji_like: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to set msg emoji like"))?;
+        Ok(())
+    }
+}
+
+/// 系统表情的id不超过3位，更长的是emoji的码点
+fn reaction_type(emoji_id: &str) -> u32 {
+    if emoji_id.len() > 3 { 2 } else { 1 }
+}
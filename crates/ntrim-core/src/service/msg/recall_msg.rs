@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use anyhow::{anyhow, Error};
+use crate::await_response;
+use crate::bot::Bot;
+use crate::servlet::olpush::msg::Contact;
+
+impl Bot {
+    /// 撤回消息，群聊只需要msg_seq，私聊还需要client_seq、msg_uid和msg_time
+    pub async fn recall_msg(self: &Arc<Bot>, contact: &Contact, msg_seq: i64, client_seq: i64, msg_uid: i64, msg_time: i64) -> Result<(), Error> {
+        let success = await_response!(tokio::time::Duration::from_secs(5), async {
+            let rx = match contact {
+                Contact::Group(_, group_id) =>
+                    Bot::_recall_group_msg(self, *group_id, msg_seq, msg_uid as u32).await,
+                Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) =>
+                    Bot::_recall_c2c_msg(self, uid.clone(), client_seq, msg_seq, msg_uid, msg_time).await,
+            };
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Tcp connection exception"))
+            }
+        }, |value: Option<bool>| {
+            value.ok_or(anyhow!("Failed to recall msg: no response"))
+        }, |err| {
+            Err(err)
+        })?;
+        if !success {
+            return Err(anyhow!("Failed to recall msg: {}", msg_seq));
+        }
+        Ok(())
+    }
+}
@@ -1,35 +1,189 @@
 use std::sync::Arc;
 use anyhow::anyhow;
+use log::warn;
+use prost::Message;
+use rand::Rng;
 use ntrim_tools::cqp::CQCode;
 use crate::await_response;
 use crate::bot::Bot;
-use crate::pb::msg::Grp;
+use crate::commands::msg_svc::send_raw_msg::next_msg_seq;
+use crate::pb::msg::{Elem, Grp, RichText, Text};
+use crate::pb::msg::elem::AioElem;
 use crate::pb::msg::send_msg_req::{C2c, RoutingHead};
+use crate::service::msg::long_msg::upload_long_msg;
+use crate::service::msg::MessageId;
 use crate::service::msg::message_factory::convert_cq_to_msg;
-use crate::servlet::olpush::msg::Contact;
+use crate::servlet::olpush::msg::{on_sent_msg, Contact};
+
+/// 单个包的RichText超过这个大小就需要走长消息或者分片发送
+const MAX_RICH_TEXT_SIZE: usize = 4096;
+
+/// 发送成功后的消息信息，撤回消息需要用到
+#[derive(Debug, Clone, Default)]
+pub struct SendMsgResult {
+    /// OneBot使用的消息id
+    pub message_id: i32,
+    /// 服务器分配的seq，和推送回来的消息一致
+    pub msg_seq: i64,
+    /// 发送时客户端自己生成的seq，撤回私聊消息的时候要用
+    pub client_seq: i64,
+    pub msg_uid: i64,
+    pub msg_time: i64,
+}
 
 impl Bot {
-    pub async fn send_msg(self: &Arc<Bot>, contact: Contact, msg: Vec<CQCode>) -> anyhow::Result<u64> {
-        let rich_text = convert_cq_to_msg(self, &contact, msg).await;
-        let routing_head = convert_contact_to_routing_head(contact);
+    pub async fn send_msg(self: &Arc<Bot>, contact: Contact, msg: Vec<CQCode>) -> anyhow::Result<SendMsgResult> {
+        let rich_text = convert_cq_to_msg(self, &contact, msg).await?;
+        Bot::send_rich_text(self, contact, rich_text).await
+    }
+
+    /// 发送成功后会记录消息id，并把消息保存下来
+    pub(crate) async fn send_rich_text(self: &Arc<Bot>, contact: Contact, rich_text: RichText) -> anyhow::Result<SendMsgResult> {
+        let routing_head = convert_contact_to_routing_head(contact.clone());
+        let sent_rich_text = rich_text.clone();
+        let mut result = if rich_text.encoded_len() <= MAX_RICH_TEXT_SIZE {
+            send_package(self, routing_head, rich_text, 1, 0, 0).await?
+        } else {
+            Bot::send_long_rich_text(self, &contact, routing_head, rich_text).await?
+        };
+        let message_id = MessageId {
+            client_seq: result.client_seq,
+            ..MessageId::new(self.unique_id, contact.clone(), result.msg_seq, result.msg_uid, result.msg_time)
+        };
+        result.message_id = self.save_message_id(message_id).await.id;
+        tokio::spawn(on_sent_msg(Arc::clone(self), contact, result.clone(), sent_rich_text));
+        Ok(result)
+    }
 
-        let result = await_response!(tokio::time::Duration::from_secs(600), async {
-            let receiver = Bot::send_raw_msg(self, routing_head, rich_text).await;
-            if let Some(receiver) = receiver {
-                receiver.await.map_err(|e| {
-                    anyhow!("Failed to send message: {}", e)
-                })
-            } else {
-                Err(anyhow!("Failed to send message: tcp connection error"))
+    /// 过长的消息默认上传到长消息服务，`LONG_MSG_MODE=split`时拆成多个分片发送
+    async fn send_long_rich_text(
+        self: &Arc<Bot>,
+        contact: &Contact,
+        routing_head: RoutingHead,
+        rich_text: RichText
+    ) -> anyhow::Result<SendMsgResult> {
+        match std::env::var("LONG_MSG_MODE").as_deref() {
+            Ok("split") => {
+                let packages = split_rich_text(rich_text, MAX_RICH_TEXT_SIZE);
+                let pkg_num = packages.len() as u64;
+                let div_seq = rand::random::<u16>() as u32;
+                // 返回的是最后一个分片，中间有分片发送失败时撤回已经发出去的分片
+                let mut sent: Vec<SendMsgResult> = Vec::with_capacity(packages.len());
+                for (pkg_index, package) in packages.into_iter().enumerate() {
+                    match send_package(self, routing_head.clone(), package, pkg_num, pkg_index as u32, div_seq).await {
+                        Ok(result) => sent.push(result),
+                        Err(e) => {
+                            for result in sent {
+                                if let Err(e) = Bot::recall_msg(self, contact, result.msg_seq, result.client_seq, result.msg_uid, result.msg_time).await {
+                                    warn!("Failed to recall sent fragment {}: {}", result.msg_seq, e);
+                                }
+                            }
+                            return Err(e);
+                        }
+                    }
+                }
+                sent.pop().ok_or(anyhow!("Failed to send message: no fragment"))
             }
-        }, |value| {
-            Ok(value)
-        }, |e| {
-            Err(e)
-        })?.ok_or(anyhow!("Failed to send message: timeout or wind ctrl"))?;
+            _ => {
+                let rich_text = upload_long_msg(self, contact, rich_text).await?;
+                send_package(self, routing_head, rich_text, 1, 0, 0).await
+            }
+        }
+    }
+}
+
+async fn send_package(
+    bot: &Arc<Bot>,
+    routing_head: RoutingHead,
+    rich_text: RichText,
+    pkg_num: u64,
+    pkg_index: u32,
+    div_seq: u32
+) -> anyhow::Result<SendMsgResult> {
+    let client_seq = next_msg_seq(bot.unique_id);
+    let random = rand::thread_rng().gen_range(1700000000..3100000000u32);
+    let rsp = await_response!(tokio::time::Duration::from_secs(600), async {
+        let receiver = Bot::send_raw_msg(bot, routing_head, rich_text, pkg_num, pkg_index, div_seq, client_seq, random).await;
+        if let Some(receiver) = receiver {
+            receiver.await.map_err(|e| {
+                anyhow!("Failed to send message: {}", e)
+            })
+        } else {
+            Err(anyhow!("Failed to send message: tcp connection error"))
+        }
+    }, |value| {
+        Ok(value)
+    }, |e| {
+        Err(e)
+    })?.ok_or(anyhow!("Failed to send message: timeout or wind ctrl"))?;
+
+    // 群聊和私聊的seq在不同的字段里面，都没有的时候无法撤回也无法分配消息id
+    let msg_seq = rsp.msg_seq.or(rsp.private_seq)
+        .ok_or(anyhow!("Failed to send message: no msg_seq in response"))?;
+    Ok(SendMsgResult {
+        message_id: 0,
+        msg_seq: msg_seq as i64,
+        client_seq: client_seq as i64,
+        msg_uid: 0x0100_0000_0000_0000 | random as i64,
+        msg_time: rsp.msg_time.map_or_else(|| chrono::Local::now().timestamp(), |t| t as i64),
+    })
+}
 
-        return Ok(result);
+/// 按大小把消息拆成多个分片，过长的文本会被切成多段
+fn split_rich_text(rich_text: RichText, max_size: usize) -> Vec<RichText> {
+    // 留一点给Elem本身的编码
+    let max_text_len = max_size - 64;
+    let mut packages = Vec::new();
+    let mut current: Vec<Elem> = Vec::new();
+    let mut current_size = 0;
+    for elem in rich_text.elems {
+        let elems = match elem.aio_elem {
+            Some(AioElem::Text(text)) if text.attr_6.is_none() && text.text.len() > max_text_len => {
+                split_text(&text.text, max_text_len).into_iter().map(|text| Elem {
+                    aio_elem: Some(AioElem::Text(Text {
+                        text,
+                        ..Default::default()
+                    }))
+                }).collect()
+            }
+            aio_elem => vec![Elem { aio_elem }]
+        };
+        for elem in elems {
+            let size = elem.encoded_len();
+            if current_size + size > max_size && !current.is_empty() {
+                packages.push(RichText {
+                    attr: None,
+                    elems: std::mem::take(&mut current),
+                });
+                current_size = 0;
+            }
+            current_size += size;
+            current.push(elem);
+        }
+    }
+    if !current.is_empty() {
+        packages.push(RichText {
+            attr: None,
+            elems: current,
+        });
     }
+    packages
+}
+
+/// 按字节数切分文本，不会切断字符
+fn split_text(text: &str, max_len: usize) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        if current.len() + c.len_utf8() > max_len {
+            result.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    result
 }
 
 fn convert_contact_to_routing_head(contact: Contact) -> RoutingHead {
@@ -62,4 +216,29 @@ fn convert_contact_to_routing_head(contact: Contact) -> RoutingHead {
             }
         },
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_split_rich_text() {
+    let text = "测试".repeat(2000);
+    let rich_text = RichText {
+        attr: None,
+        elems: vec![Elem {
+            aio_elem: Some(AioElem::Text(Text {
+                text: text.clone(),
+                ..Default::default()
+            }))
+        }],
+    };
+    let packages = split_rich_text(rich_text, MAX_RICH_TEXT_SIZE);
+    assert_eq!(packages.len(), 3);
+    assert!(packages.iter().all(|p| p.encoded_len() <= MAX_RICH_TEXT_SIZE));
+    let joined = packages.into_iter()
+        .flat_map(|p| p.elems)
+        .filter_map(|elem| match elem.aio_elem {
+            Some(AioElem::Text(text)) => Some(text.text),
+            _ => None
+        })
+        .collect::<String>();
+    assert_eq!(joined, text);
+}
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use anyhow::Error;
+use crate::await_response;
+use crate::bot::Bot;
+use crate::Contact;
+
+impl Bot {
+    /// 戳一戳，群聊中`target`为群成员，好友中`target`为好友或者自己
+    pub async fn send_poke(self: &Arc<Self>, contact: Contact, target: i64) -> Result<(), Error> {
+        let (group_id, friend_uin) = match contact {
+            Contact::Group(_, group_id) => (Some(group_id), None),
+            Contact::Friend(_, uin, _) | Contact::Stranger(_, uin, _) => (None, Some(uin)),
+        };
+        await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = Bot::_send_poke(self, group_id, friend_uin, target).await;
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to send_poke: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to send poke"))?;
+        Ok(())
+    }
+}
@@ -1,11 +1,15 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use anyhow::Error;
-use tokio::sync::Mutex;
-use once_cell::unsync::Lazy;
+use log::{debug, warn};
+use tokio::sync::RwLock;
+use once_cell::sync::Lazy;
 use crate::await_response;
 use crate::bot::Bot;
 
+/// rkey剩余有效期低于该值时在后台提前刷新
+const RKEY_REFRESH_AHEAD_SEC: i64 = 600;
+
 #[derive(Debug, Clone)]
 pub struct RKey {
     pub flag: u8,
@@ -27,42 +31,63 @@ impl RKey {
     pub fn is_expired(&self) -> bool {
         self.expire_time < chrono::Local::now().timestamp()
     }
+
+    pub fn is_expiring(&self) -> bool {
+        self.expire_time - RKEY_REFRESH_AHEAD_SEC < chrono::Local::now().timestamp()
+    }
 }
 
-static mut RKEY: Lazy<Mutex<HashMap<u8, RKey>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// (bot_uin, flag) -> rkey
+static RKEY: Lazy<RwLock<HashMap<(i64, u8), RKey>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+// 正在后台刷新rkey的bot
+static REFRESHING: Lazy<Mutex<HashSet<i64>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+async fn refresh_rkey(bot: &Arc<Bot>) -> Result<(), Error> {
+    let rsp = await_response!(tokio::time::Duration::from_secs(5), async {
+        let rx = Bot::request_download_rkey(bot).await;
+        if let Some(rx) = rx {
+            rx.await.map_err(|e| Error::new(e))
+        } else {
+            Err(Error::msg("Unable to get download rkey: tcp connection exception"))
+        }
+    }, |value| {
+        Ok(value)
+    }, |e| {
+        Err(e)
+    })?.ok_or(Error::msg("DownloadRKeyRsp is none"))?;
+    let mut cache = RKEY.write().await;
+    for key in rsp.rkeys {
+        let Some(r#type) = key.r#type else { continue };
+        let r#type = r#type as u8;
+        let create_time = key.rkey_create_time
+            .map_or(chrono::Local::now().timestamp(), |v| v as i64);
+        cache.insert((bot.unique_id, r#type), RKey::new(
+            r#type, key.rkey, key.rkey_ttl_sec as i32, create_time + key.rkey_ttl_sec as i64
+        ));
+    }
+    debug!("Refreshed download rkey for bot {}", bot.unique_id);
+    Ok(())
+}
 
+/// 获取下载用的rkey，过期时同步刷新，快过期时在后台刷新
 pub async fn get_download_reky(bot: &Arc<Bot>, flag: u8) -> Result<Option<RKey>, Error> {
-    let refresh_rkey = || async {
-        let value = await_response!(tokio::time::Duration::from_secs(5), async {
-            let rx = Bot::request_download_rkey(bot).await;
-            if let Some(rx) = rx {
-                rx.await.map_err(|e| Error::new(e))
-            } else {
-                Err(Error::msg("Unable to get download rkey: tcp connection exception"))
-            }
-        }, |value| {
-            Ok(value)
-        }, |e| {
-            Err(e)
-        });
-        value
-    };
-    let is_rkey_expires = unsafe { RKEY.lock().await.get(&flag) }
-        .map_or(true, |v| v.is_expired());
-    if is_rkey_expires {
-        let rsp = match refresh_rkey().await? {
-            Some(rsp) => rsp,
-            None => return Err(Error::msg("DownloadRKeyRsp is none")),
-        };;
-        unsafe {
-            for key in rsp.rkeys {
-                let r#type = key.r#type.unwrap() as u8;
-                RKEY.lock().await.insert(r#type, RKey::new(
-                    r#type, key.rkey, key.rkey_ttl_sec as i32, key.rkey_create_time.unwrap() as i64 + key.rkey_ttl_sec as i64
-                ));
+    let rkey = RKEY.read().await.get(&(bot.unique_id, flag)).cloned();
+    match rkey {
+        Some(rkey) if !rkey.is_expired() => {
+            if rkey.is_expiring() && REFRESHING.lock().unwrap().insert(bot.unique_id) {
+                let bot = bot.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = refresh_rkey(&bot).await {
+                        warn!("Failed to refresh download rkey in background: {}", e);
+                    }
+                    REFRESHING.lock().unwrap().remove(&bot.unique_id);
+                });
             }
+            Ok(Some(rkey))
         }
-    };
-
-    Ok(unsafe { RKEY.lock().await.get(&flag).cloned() })
-}
\ No newline at end of file
+        _ => {
+            refresh_rkey(bot).await?;
+            Ok(RKEY.read().await.get(&(bot.unique_id, flag)).cloned())
+        }
+    }
+}
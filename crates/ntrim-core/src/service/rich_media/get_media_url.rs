@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use anyhow::{anyhow, Error};
+use crate::await_response;
+use crate::bot::Bot;
+use crate::pb::msg::IndexNode;
+use crate::servlet::olpush::msg::Contact;
+use crate::service::rich_media::get_download_reky;
+
+const DEFAULT_PIC_DOMAIN: &str = "multimedia.nt.qq.com";
+
+impl Bot {
+    /// 拼接NT图片的完整下载链接，rkey 10为私聊，20为群聊
+    pub async fn get_image_url(
+        self: &Arc<Bot>,
+        contact: &Contact,
+        domain: Option<String>,
+        url_path: &str
+    ) -> Result<String, Error> {
+        let flag = match contact {
+            Contact::Group(..) => 20,
+            _ => 10,
+        };
+        let rkey = get_download_reky(self, flag).await?
+            .ok_or(anyhow!("No download rkey for type {}", flag))?;
+        let domain = domain.unwrap_or_else(|| DEFAULT_PIC_DOMAIN.to_string());
+        Ok(format!("https://{}{}&spec=0{}", domain, url_path, rkey.key))
+    }
+
+    pub async fn get_video_url(self: &Arc<Bot>, contact: &Contact, node: IndexNode) -> Result<String, Error> {
+        await_response!(tokio::time::Duration::from_secs(5), async {
+            let rx = match contact {
+                Contact::Group(_, group_id) => Bot::_request_group_video_url(self, *group_id, node).await,
+                Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) =>
+                    Bot::_request_c2c_video_url(self, uid.clone(), node).await,
+            };
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to get video url: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to get video url"))
+    }
+
+    pub async fn get_ptt_url(self: &Arc<Bot>, contact: &Contact, node: IndexNode) -> Result<String, Error> {
+        await_response!(tokio::time::Duration::from_secs(5), async {
+            let rx = match contact {
+                Contact::Group(_, group_id) => Bot::_request_group_ptt_url(self, *group_id, node).await,
+                Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) =>
+                    Bot::_request_c2c_ptt_url(self, uid.clone(), node).await,
+            };
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Unable to get ptt url: tcp connection exception"))
+            }
+        }, |value| {
+            Ok(value)
+        }, |e| {
+            Err(e)
+        })?.ok_or(Error::msg("Failed to get ptt url"))
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use anyhow::Error;
+use prost::Message;
+use sha1::{Digest, Sha1};
+use crate::bot::Bot;
+use crate::pb::bdh::{Ip, Network, NtHighwayHash, Opt, RichMediaExt};
+use crate::pb::msg::MsgInfoBody;
+use crate::pb::trpc::rich_media_ntv2::Ipv4;
+use crate::service::bdh;
+
+/// 通过BDH把资源上传到NT的highway
+pub(crate) async fn upload_nt_resource(
+    bot: &Arc<Bot>,
+    cmd_id: u32,
+    ukey: String,
+    ipv4: &[Ipv4],
+    msg_info_body: MsgInfoBody,
+    data: Vec<u8>,
+    original: bool
+) -> Result<(), Error> {
+    let md5 = md5::compute(&data).to_vec();
+    let mut hasher = Sha1::new();
+    hasher.update(&data);
+    let sha1 = hasher.finalize().to_vec();
+    let ips = ipv4.iter().map(|item| {
+        let ip = item.in_ip.unwrap_or(0);
+        let in_port = item.in_port.unwrap_or(0);
+        let in_ip = format!("{}.{}.{}.{}", ip & 0xff, (ip >> 8) & 0xff, (ip >> 16) & 0xff, ip >> 24);
+        crate::pb::bdh::Ipv4 {
+            ip: Some(Ip {
+                enable: 1,
+                ip: in_ip
+            }),
+            port: in_port as u32
+        }
+    }).collect::<Vec<_>>();
+
+    let max_chunk_size = std::env::var("BDH_CHUNK_SIZE")
+        .map_or(1024 * 500, |size| size.parse::<usize>().unwrap());
+    let ext = RichMediaExt {
+        file_uuid: msg_info_body.index.file_uuid.clone(),
+        up_key: ukey,
+        original: if original { 1 } else { 0 },
+        opt: Some(Opt {
+            switch_1: 1,
+            switch_4: 1
+        }),
+        network: Some(Network {
+            addrs_v4: ips
+        }),
+        msg_info_body: vec![msg_info_body],
+        block_size: 1048576,
+        nt_highway_hash: Some(NtHighwayHash {
+            sha1: vec![sha1]
+        }),
+    }.encode_to_vec();
+
+    bdh::upload_resource_no_resp(
+        bot.clone(),
+        "PicUp.DataUp".to_string(),
+        cmd_id,
+        max_chunk_size,
+        data,
+        md5,
+        ext
+    ).await?;
+    Ok(())
+}
@@ -1,8 +1,14 @@
 pub mod get_downlaod_rkey;
 pub mod request_upload_group_res;
 pub mod request_upload_c2c_pic;
+pub mod request_upload_ptt;
+pub mod request_upload_video;
 pub mod get_upload_ukey;
+pub mod get_media_url;
+pub mod resource;
+mod highway;
 
 pub use get_downlaod_rkey::RKey;
 pub use get_downlaod_rkey::get_download_reky;
+pub use request_upload_group_res::PicUploadOptions;
 
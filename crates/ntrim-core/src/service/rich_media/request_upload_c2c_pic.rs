@@ -0,0 +1,15 @@
+use std::sync::Arc;
+use crate::bot::Bot;
+use crate::pb::msg::MsgInfo;
+use crate::service::rich_media::request_upload_group_res::PicUploadOptions;
+
+impl Bot {
+    pub async fn upload_c2c_pic(
+        self: &Arc<Bot>,
+        friend_uid: String,
+        pic_bytes: Vec<u8>,
+        options: PicUploadOptions
+    ) -> anyhow::Result<MsgInfo> {
+        self.upload_pic(&crate::servlet::olpush::msg::Contact::Friend("".to_string(), 0, friend_uid), pic_bytes, options).await
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use anyhow::{anyhow, Error};
+use sha1::{Digest, Sha1};
+use ntrim_tools::audio::Silk;
+use crate::await_response;
+use crate::bot::Bot;
+use crate::pb::msg::{ExtBizInfo, MsgInfo, PicExtBizInfo, PttExtBizInfo, VideoExtBizInfo};
+use crate::pb::trpc::rich_media_ntv2::{FileInfo, FileType, UploadRsp};
+use crate::service::rich_media::highway::upload_nt_resource;
+use crate::servlet::olpush::msg::Contact;
+
+/// 语音最大10MB
+pub const MAX_PTT_SIZE: usize = 10 * 1024 * 1024;
+
+impl Bot {
+    /// 上传silk语音，群聊走0x126e，私聊走0x126d
+    pub async fn upload_ptt(self: &Arc<Bot>, contact: &Contact, silk: Silk) -> anyhow::Result<MsgInfo> {
+        let ptt_bytes = silk.data;
+        let mut hasher = Sha1::new();
+        hasher.update(&ptt_bytes);
+        let sha1 = hasher.finalize().to_vec();
+        let md5 = md5::compute(&ptt_bytes).to_vec();
+        let md5_str = hex::encode(&md5);
+        let file_info = FileInfo {
+            file_size: Some(ptt_bytes.len() as u64),
+            md5: Some(md5_str.clone()),
+            sha1: Some(hex::encode(&sha1)),
+            name: Some(md5_str + ".amr"),
+            file_type: Some(FileType {
+                file_type: Some(3),
+                pic_format: Some(0),
+                video_format: Some(0),
+                voice_format: Some(1),
+            }),
+            width: Some(0),
+            height: Some(0),
+            time: Some(silk.duration),
+            original: Some(0),
+        };
+        let is_group = matches!(contact, Contact::Group(..));
+        let ext_biz_info = ExtBizInfo {
+            pic: Some(PicExtBizInfo {
+                text_summary: Some("".to_string()),
+                ..Default::default()
+            }),
+            video: Some(VideoExtBizInfo {
+                bytes_pb_reserve: Some(vec![]),
+                ..Default::default()
+            }),
+            ptt: Some(if is_group {
+                PttExtBizInfo {
+                    bytes_reserve: Some(vec![]),
+                    bytes_pb_reserve: Some(vec![]),
+                    bytes_general_flags: Some(vec![0x9a, 0x01, 0x07, 0xaa, 0x03, 0x04, 0x08, 0x08, 0x12, 0x00]),
+                    ..Default::default()
+                }
+            } else {
+                PttExtBizInfo {
+                    bytes_reserve: Some(vec![0x08, 0x00, 0x38, 0x00]),
+                    bytes_pb_reserve: Some(vec![]),
+                    bytes_general_flags: Some(vec![0x9a, 0x01, 0x0b, 0xaa, 0x03, 0x08, 0x08, 0x04, 0x12, 0x04, 0x00, 0x00, 0x00, 0x00]),
+                    ..Default::default()
+                }
+            }),
+            ..Default::default()
+        };
+        let upload = await_response!(tokio::time::Duration::from_secs(5), async {
+            let rx = match contact {
+                Contact::Group(_, group_id) =>
+                    Bot::_request_upload_group_ptt(self, *group_id, file_info, ext_biz_info).await,
+                Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) =>
+                    Bot::_request_upload_c2c_ptt(self, uid.clone(), file_info, ext_biz_info).await,
+            };
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Tcp connection exception"))
+            }
+        }, |value: Option<UploadRsp>| {
+            value.ok_or(anyhow!("Failed to upload ptt: no upload info"))
+        }, |err| {
+            Err(err)
+        })?;
+
+        let msg_info = upload.msg_info
+            .ok_or(anyhow!("Failed to upload ptt: no msg_info"))?;
+        let Some(ukey) = upload.ukey else {
+            // 服务器已经有这个文件了
+            return Ok(msg_info)
+        };
+        let msg_info_body = msg_info.msg_info_body.first()
+            .ok_or(anyhow!("Failed to upload ptt: no msg_info_body"))?
+            .clone();
+
+        upload_nt_resource(
+            self,
+            if is_group { 1008 } else { 1007 },
+            ukey,
+            &upload.ipv4,
+            msg_info_body,
+            ptt_bytes,
+            false
+        ).await?;
+
+        Ok(msg_info)
+    }
+}
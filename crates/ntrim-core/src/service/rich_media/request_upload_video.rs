@@ -0,0 +1,184 @@
+use std::io::Cursor;
+use std::sync::Arc;
+use anyhow::{anyhow, Error};
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgb, RgbImage};
+use log::warn;
+use sha1::{Digest, Sha1};
+use ntrim_tools::video::{parse_mp4_info, VideoInfo};
+use crate::await_response;
+use crate::bot::Bot;
+use crate::pb::msg::{ExtBizInfo, MsgInfo, PicExtBizInfo, PttExtBizInfo, VideoExtBizInfo};
+use crate::pb::trpc::rich_media_ntv2::{FileInfo, FileType, UploadRsp};
+use crate::service::rich_media::highway::upload_nt_resource;
+use crate::servlet::olpush::msg::Contact;
+
+/// 视频最大100MB
+pub const MAX_VIDEO_SIZE: usize = 100 * 1024 * 1024;
+/// 自动生成的封面最大宽度
+const THUMB_MAX_WIDTH: u32 = 320;
+
+fn file_info(data: &[u8], name_ext: &str, file_type: FileType, width: u32, height: u32, time: u32) -> FileInfo {
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    let md5 = hex::encode(md5::compute(data).as_slice());
+    FileInfo {
+        file_size: Some(data.len() as u64),
+        md5: Some(md5.clone()),
+        sha1: Some(hex::encode(hasher.finalize())),
+        name: Some(md5 + name_ext),
+        file_type: Some(file_type),
+        width: Some(width),
+        height: Some(height),
+        time: Some(time),
+        original: Some(0),
+    }
+}
+
+impl Bot {
+    /// 上传视频和封面，群聊走0x11ea，私聊走0x11e9
+    ///
+    /// 没有传封面时需要`PATH`里面有ffmpeg来截取第一帧，否则只能用纯黑的封面
+    pub async fn upload_video(
+        self: &Arc<Bot>,
+        contact: &Contact,
+        video_bytes: Vec<u8>,
+        thumb_bytes: Option<Vec<u8>>
+    ) -> anyhow::Result<MsgInfo> {
+        let info = parse_mp4_info(&video_bytes).unwrap_or_else(|| {
+            warn!("Failed to parse video info, maybe not a mp4 file");
+            VideoInfo::default()
+        });
+        let thumb_bytes = match thumb_bytes {
+            Some(thumb) => thumb,
+            None => generate_thumbnail(&video_bytes, &info).await?,
+        };
+        let (thumb_width, thumb_height) = image::load_from_memory(&thumb_bytes)
+            .map_or((info.width, info.height), |img| img.dimensions());
+
+        let video_info = file_info(&video_bytes, ".mp4", FileType {
+            file_type: Some(2),
+            pic_format: Some(0),
+            video_format: Some(0),
+            voice_format: Some(0),
+        }, info.width, info.height, info.duration);
+        let thumb_info = file_info(&thumb_bytes, ".jpg", FileType {
+            file_type: Some(1),
+            pic_format: Some(0),
+            video_format: Some(0),
+            voice_format: Some(0),
+        }, thumb_width, thumb_height, 0);
+        let ext_biz_info = ExtBizInfo {
+            pic: Some(PicExtBizInfo {
+                biz_type: Some(0),
+                text_summary: Some("".to_string()),
+                ..Default::default()
+            }),
+            video: Some(VideoExtBizInfo {
+                bytes_pb_reserve: Some(vec![0x80, 0x01, 0x00]),
+                ..Default::default()
+            }),
+            ptt: Some(PttExtBizInfo {
+                bytes_reserve: Some(vec![]),
+                bytes_pb_reserve: Some(vec![]),
+                bytes_general_flags: Some(vec![]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let upload = await_response!(tokio::time::Duration::from_secs(10), async {
+            let rx = match contact {
+                Contact::Group(_, group_id) =>
+                    Bot::_request_upload_group_video(self, *group_id, video_info, thumb_info, ext_biz_info).await,
+                Contact::Friend(_, _, uid) | Contact::Stranger(_, _, uid) =>
+                    Bot::_request_upload_c2c_video(self, uid.clone(), video_info, thumb_info, ext_biz_info).await,
+            };
+            if let Some(rx) = rx {
+                rx.await.map_err(|e| Error::new(e))
+            } else {
+                Err(Error::msg("Tcp connection exception"))
+            }
+        }, |value: Option<UploadRsp>| {
+            value.ok_or(anyhow!("Failed to upload video: no upload info"))
+        }, |err| {
+            Err(err)
+        })?;
+
+        let msg_info = upload.msg_info
+            .ok_or(anyhow!("Failed to upload video: no msg_info"))?;
+        let is_group = matches!(contact, Contact::Group(..));
+
+        if let Some(ukey) = upload.ukey {
+            let video_body = msg_info.msg_info_body.first()
+                .ok_or(anyhow!("Failed to upload video: no msg_info_body"))?
+                .clone();
+            upload_nt_resource(
+                self,
+                if is_group { 1005 } else { 1001 },
+                ukey,
+                &upload.ipv4,
+                video_body,
+                video_bytes,
+                false
+            ).await?;
+        }
+
+        // 封面的ukey在sub_file_infos里面
+        let thumb = upload.sub_file_infos.into_iter()
+            .find(|v| v.ukey.is_some());
+        if let (Some(thumb), Some(thumb_body)) = (thumb, msg_info.msg_info_body.get(1)) {
+            upload_nt_resource(
+                self,
+                if is_group { 1006 } else { 1002 },
+                thumb.ukey.unwrap(),
+                &thumb.ipv4,
+                thumb_body.clone(),
+                thumb_bytes,
+                false
+            ).await?;
+        }
+
+        Ok(msg_info)
+    }
+}
+
+/// 优先用ffmpeg截取第一帧，没有ffmpeg或者截取失败的时候生成一张纯色封面
+async fn generate_thumbnail(video: &[u8], info: &VideoInfo) -> Result<Vec<u8>, Error> {
+    match extract_first_frame(video).await {
+        Ok(frame) => return Ok(frame),
+        Err(e) => warn!("Failed to extract video frame, use a black cover instead \
+            (install ffmpeg or set the `cover` of the video): {}", e),
+    }
+    let (width, height) = if info.width > 0 && info.height > 0 {
+        let width = info.width.min(THUMB_MAX_WIDTH);
+        (width, (info.height as u64 * width as u64 / info.width as u64).max(1) as u32)
+    } else {
+        (THUMB_MAX_WIDTH, THUMB_MAX_WIDTH * 9 / 16)
+    };
+    let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([0, 0, 0])));
+    let mut thumb = Cursor::new(Vec::new());
+    image.write_to(&mut thumb, ImageFormat::Jpeg)?;
+    Ok(thumb.into_inner())
+}
+
+async fn extract_first_frame(video: &[u8]) -> Result<Vec<u8>, Error> {
+    let name = uuid::Uuid::new_v4().to_string();
+    let input = std::env::temp_dir().join(format!("{}.mp4", name));
+    let output = std::env::temp_dir().join(format!("{}.jpg", name));
+    tokio::fs::write(&input, video).await?;
+    let status = tokio::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&input)
+        .args(["-frames:v", "1", "-f", "image2"])
+        .arg(&output)
+        .status()
+        .await;
+    let _ = tokio::fs::remove_file(&input).await;
+    let frame = match status {
+        Ok(status) if status.success() => tokio::fs::read(&output).await.map_err(Error::from),
+        Ok(status) => Err(anyhow!("ffmpeg exited with {}", status)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(anyhow!("ffmpeg is not found in PATH")),
+        Err(e) => Err(anyhow!("Failed to run ffmpeg: {}", e)),
+    };
+    let _ = tokio::fs::remove_file(&output).await;
+    frame
+}
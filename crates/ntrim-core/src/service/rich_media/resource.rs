@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Error};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::ImageFormat;
+
+/// 图片最大30MB
+pub const MAX_PIC_SIZE: usize = 30 * 1024 * 1024;
+
+/// 读取富媒体资源，支持`http(s)://`、`base64://`、`file://`以及本地路径
+pub(crate) async fn fetch_resource(src: &str, max_size: usize) -> Result<Vec<u8>, Error> {
+    let data = if src.starts_with("http://") || src.starts_with("https://") {
+        let mut response = reqwest::get(src).await?.error_for_status()?;
+        if response.content_length().is_some_and(|len| len as usize > max_size) {
+            return Err(anyhow!("Resource too large: {}", src));
+        }
+        // 没有Content-Length的时候边下载边检查大小
+        let mut data = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if data.len() + chunk.len() > max_size {
+                return Err(anyhow!("Resource too large: {}", src));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        data
+    } else if let Some(data) = src.strip_prefix("base64://") {
+        STANDARD.decode(data.trim())?
+    } else {
+        let path = src.strip_prefix("file://").unwrap_or(src);
+        // windows: file:///C:/Users/1234567890.jpg
+        let path = if cfg!(windows) { path.trim_start_matches('/') } else { path };
+        let len = tokio::fs::metadata(path).await
+            .map_err(|e| anyhow!("Failed to read resource {}: {}", path, e))?
+            .len();
+        if len > max_size as u64 {
+            return Err(anyhow!("Resource too large: {} > {}", len, max_size));
+        }
+        tokio::fs::read(path).await
+            .map_err(|e| anyhow!("Failed to read resource {}: {}", path, e))?
+    };
+    if data.is_empty() {
+        return Err(anyhow!("Empty resource: {}", src));
+    }
+    if data.len() > max_size {
+        return Err(anyhow!("Resource too large: {} > {}", data.len(), max_size));
+    }
+    Ok(data)
+}
+
+/// 根据文件头识别图片格式，返回(扩展名, pic_format)
+pub(crate) fn sniff_pic_format(data: &[u8]) -> Result<(&'static str, u32), Error> {
+    match image::guess_format(data) {
+        Ok(ImageFormat::Jpeg) => Ok((".jpg", 1000)),
+        Ok(ImageFormat::Png) => Ok((".png", 1001)),
+        Ok(ImageFormat::WebP) => Ok((".webp", 1002)),
+        Ok(ImageFormat::Bmp) => Ok((".bmp", 1005)),
+        Ok(ImageFormat::Gif) => Ok((".gif", 2000)),
+        Ok(format) => Err(anyhow!("Unsupported image format: {:?}", format)),
+        Err(_) => Err(anyhow!("Unrecognized image content")),
+    }
+}
+
+#[test]
+fn test_sniff_pic_format() {
+    assert_eq!(sniff_pic_format(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]).unwrap(), (".png", 1001));
+    assert_eq!(sniff_pic_format(b"GIF89a").unwrap(), (".gif", 2000));
+    assert!(sniff_pic_format(b"hello world").is_err());
+}
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+use log::warn;
+use crate::bot::Bot;
+#[cfg(feature = "sql")]
+use crate::commands::troop::GroupMemberInfo;
+
+impl Bot {
+    /// 通过uid获取群成员的uin，缓存中不存在时会刷新群成员列表
+    pub async fn get_troop_member_uin(self: &Arc<Self>, group_id: i64, uid: &str) -> Option<i64> {
+        if uid.is_empty() {
+            return None;
+        }
+        if uid == self.client.session.read().await.uid {
+            return Some(self.unique_id);
+        }
+        #[cfg(feature = "sql")]
+        if crate::db::is_initialized() {
+            let pool = crate::db::PG_POOL.get().unwrap();
+            if let Ok(uin) = GroupMemberInfo::query_uin_by_uid(pool, group_id, uid).await {
+                return Some(uin);
+            }
+        }
+        let owner_uin = match self.get_troop_info(group_id).await {
+            Ok(info) => info.owner_uin,
+            Err(e) => {
+                warn!("Failed to get troop info for {}: {:?}", group_id, e);
+                return None;
+            }
+        };
+        match self.get_troop_member_list(group_id, owner_uin).await {
+            Ok(list) => list.into_iter().find(|m| m.uid == uid).map(|m| m.uin),
+            Err(e) => {
+                warn!("Failed to get troop member list for {}: {:?}", group_id, e);
+                None
+            }
+        }
+    }
+}
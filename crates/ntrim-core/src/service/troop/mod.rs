@@ -2,4 +2,4 @@ mod get_troop_info;
 mod get_troop_list;
 mod get_troop_member_list;
 mod get_troop_member_card_info;
-
+mod get_troop_member_uin;
@@ -0,0 +1,79 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::sync::Mutex;
+use bytes::Bytes;
+use once_cell::sync::Lazy;
+
+//           from_uin msg_seq msg_cookies
+pub(super) type PushKey = (i64, i16, Bytes);
+
+static MSG_PUSHED: Lazy<Mutex<DedupWindow<i64>>> = Lazy::new(|| {
+    Mutex::new(DedupWindow::new(dedup_window_size()))
+});
+
+static LEGACY_PUSHED: Lazy<Mutex<DedupWindow<PushKey>>> = Lazy::new(|| {
+    Mutex::new(DedupWindow::new(dedup_window_size()))
+});
+
+fn dedup_window_size() -> usize {
+    std::env::var("PUSH_DEDUP_WINDOW").map_or(2048, |v| v.parse::<usize>().unwrap())
+}
+
+/// 重连和多端同步的时候服务器会重复下发同一条消息，通过`msg_uid`去重
+pub(super) fn is_duplicate_msg(msg_uid: i64) -> bool {
+    if msg_uid == 0 {
+        return false;
+    }
+    MSG_PUSHED.lock().unwrap().check(msg_uid)
+}
+
+/// 旧版推送没有`msg_uid`，通过`from_uin`、`msg_seq`和`msg_cookies`去重
+pub(super) fn is_duplicate_push(key: PushKey) -> bool {
+    LEGACY_PUSHED.lock().unwrap().check(key)
+}
+
+/// 有界的去重窗口，超过容量后淘汰最早的记录
+struct DedupWindow<K> {
+    capacity: usize,
+    set: HashSet<K>,
+    queue: VecDeque<K>,
+}
+
+impl<K: Eq + Hash + Clone> DedupWindow<K> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            set: HashSet::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// 已经出现过时返回true，否则记录下来并返回false
+    fn check(&mut self, key: K) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        if !self.set.insert(key.clone()) {
+            return true;
+        }
+        self.queue.push_back(key);
+        if self.queue.len() > self.capacity {
+            if let Some(old) = self.queue.pop_front() {
+                self.set.remove(&old);
+            }
+        }
+        false
+    }
+}
+
+#[test]
+fn test_dedup_window() {
+    let mut window = DedupWindow::new(2);
+    assert!(!window.check(1));
+    assert!(window.check(1));
+    assert!(!window.check(2));
+    assert!(!window.check(3));
+    // 1已经被淘汰
+    assert!(!window.check(1));
+    assert!(window.check(3));
+}
@@ -1,12 +1,14 @@
 pub mod msg;
 pub mod notice;
+mod online_push;
+mod dedup;
 
 use std::collections::HashMap;
 use std::sync::Arc;
 use anyhow::Error;
 use bytes::{Buf, Bytes};
 use jcers::{Jce, JcePut};
-use log::{error, warn};
+use log::{debug, error, warn};
 use prost::Message;
 use ntrim_macros::servlet;
 use ntrim_tools::tokiort::global_tokio_runtime;
@@ -49,9 +51,14 @@ impl OlPushServlet {
 
     async fn on_msg_push(bot: Arc<Bot>, mut from: FromServiceMsg) -> Result<(), Error> {
         let msg = MsgPush::decode(Bytes::from(from.wup_buffer.clone()))?.msg;
+        if dedup::is_duplicate_msg(msg.content_head.msg_uid) {
+            debug!("Duplicate msg push, type: {}, uid: {}", msg.content_head.msg_type, msg.content_head.msg_uid);
+            return Ok(());
+        }
         match msg.content_head.msg_type {
             //33 => notice::on_group_member_increase(bot, msg_push),
             //38 => notice::on_group_create(bot, msg_push),
+            44 => notice::on_group_admin_change(bot, msg).await,
 
             82 => msg::on_group_msg(bot, msg).await,
             //84 => notice::on_group_join_request(bot, msg_push),
@@ -59,14 +66,17 @@ impl OlPushServlet {
             //87 => notice::on_group_invite(bot, msg_push),
 
             //141 => msg::on_stranger_msg(bot, msg_push),
-            //166 => msg::on_friend_msg(bot, msg_push),
+            166 => msg::on_friend_msg(bot, msg).await,
             //167 => msg::on_unidirectional_friend_msg(bot, msg_push),
-            //187 => notice::on_friend_request_add(bot, msg_push),
-            //191 => notice::on_unidirectional_friend_increase(bot, msg_push),
+            187 => notice::on_friend_request_add(bot, msg).await,
+            191 => notice::on_unidirectional_friend_increase(bot, msg).await,
             //208 => msg::on_friend_audio_msg(bot, msg_push),
 
             //525 => notice::on_group_member_invite(bot, msg_push),
-            //529 => notice::on_offline_file(bot, msg_push),
+            528 => notice::on_c2c_notice(bot, msg).await,
+            529 => notice::on_offline_file(bot, msg).await,
+
+            732 => notice::on_group_notice(bot, msg).await,
 
             _ => if std::env::var("ENABLE_PRINT_UNKNOWN_PUSH").map_or(true, |v| v.parse::<bool>().unwrap()) {
                 warn!("Unknown msg type: {:?}, buf: {}", msg.content_head.msg_type, hex::encode(&from.wup_buffer))
@@ -97,12 +107,12 @@ impl OlPushServlet {
             svrip: 0,
             push_token: Bytes::new(),
             del_infos: msg_infos
-                .into_iter()
+                .iter()
                 .map(|m| DelMsgInfo {
                     from_uin: m.from_uin,
                     msg_time: m.msg_time,
                     msg_seq: m.msg_seq,
-                    msg_cookies: m.msg_cookies,
+                    msg_cookies: m.msg_cookies.clone(),
                     ..Default::default()
                 })
                 .collect(),
@@ -123,6 +133,10 @@ impl OlPushServlet {
         let payload = pkt.freeze().to_vec();
         let pkt = UniPacket::new_service("OnlinePush.RespPush".into(), payload);
         let _ = bot.client.send_uni_packet(pkt).await;
+
+        for info in msg_infos {
+            online_push::on_push_message(Arc::clone(&bot), info).await;
+        }
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,20 +1,47 @@
+use std::collections::HashMap;
 use std::fmt::format;
 use std::sync::Arc;
+use anyhow::Error;
 use bytes::{Buf, Bytes};
 use log::{error, warn};
 use prost::Message;
-use ntrim_tools::cqp::{At, Face, Image, Reply};
+use ntrim_tools::cqp::{At, Basketball, CustomMusic, Face, File, Forward, Image, Json, Location, MFace, Music, NewDice, NewRPS, Record, Reply, Share, Video, Xml};
+use ntrim_tools::flate2::decompress_deflate;
 pub use ntrim_tools::cqp::CQCode;
 use crate::bot::Bot;
+use crate::service::msg::MessageId;
 use crate::pb::msg::elem::AioElem;
 use crate::pb::msg::{ * };
 use crate::pb::msg::common_elem::{CommonBigFaceElem, CommonFaceElem};
 use crate::pb::trpc::olpush::{ * };
 use crate::servlet::olpush::msg::{Contact, MessageRecord};
 
-pub(crate) async fn parse_elements(bot: &Arc<Bot>, record: &mut MessageRecord, elems: Vec<Elem>) {
+pub(crate) const DICE_FACE_ID: u32 = 358;
+pub(crate) const RPS_FACE_ID: u32 = 359;
+pub(crate) const BASKETBALL_FACE_ID: u32 = 114;
+const DICE_TAB_ID: u32 = 11464;
+const RPS_TAB_ID: u32 = 11415;
+
+/// 长消息最多展开的层数，嵌套或者引用自己的长消息不会无限展开
+const MAX_LONG_MSG_DEPTH: u32 = 3;
+
+/// 解析消息时是否访问网络
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ParseMode {
+    /// 请求图片、视频、语音的下载链接并展开长消息，`depth`为当前所在长消息的层数
+    Online { depth: u32 },
+    /// 数据库里读出来的、自己发出去的消息，不请求下载链接也不展开长消息
+    Offline,
+}
+
+pub(crate) async fn parse_elements(bot: &Arc<Bot>, record: &mut MessageRecord, elems: Vec<Elem>, mode: ParseMode) {
     let mut single_element = false;
     let mut is_front_reply = 0; // 跳过下一条艾特消息，因为这个破消息是为了兼容不支持回复的客户端实现的！
+    let contact = record.contact.clone();
+    let mut media_urls = match mode {
+        ParseMode::Online { .. } => resolve_media_urls(bot, &contact, &elems).await,
+        ParseMode::Offline => HashMap::new(),
+    };
     let result = &mut record.elements;
     for elem in elems {
         if elem.aio_elem.is_none() {
@@ -29,8 +56,14 @@ pub(crate) async fn parse_elements(bot: &Arc<Bot>, record: &mut MessageRecord, e
             }
             continue
         }
-        // 毛都没有，就堆气泡什么的
-        if let AioElem::GeneralFlags(GeneralFlags { .. }) = elem {
+        // 毛都没有，就堆气泡什么的，长消息的res_id也在这里面
+        if let AioElem::GeneralFlags(GeneralFlags { long_text_flag, long_text_resid, .. }) = elem {
+            if let (Some(1), Some(res_id)) = (long_text_flag, long_text_resid) {
+                if let Some(elements) = expand_long_msg(bot, res_id, mode).await {
+                    replace_long_msg_body(result, elements);
+                    single_element = true;
+                }
+            }
             continue
         }
         // 同上
@@ -46,12 +79,16 @@ pub(crate) async fn parse_elements(bot: &Arc<Bot>, record: &mut MessageRecord, e
         match elem {
             AioElem::Text(Text { text, attr_6, .. }) => {
                 // attr_6是一个Option<Bytes>，如果有值，那么就是一个At，否则就是一个普通的文本消息
-                if attr_6.is_some() {
+                if let Some(attr_6) = attr_6 {
                     if is_front_reply == 2 {
                         is_front_reply -= 1;
                         continue;
                     }
-                    let mut buf = Bytes::from(attr_6.unwrap());
+                    let mut buf = Bytes::from(attr_6);
+                    if buf.remaining() < 11 {
+                        warn!("Invalid at attr_6: {}", hex::encode(&buf));
+                        continue;
+                    }
                     let size = buf.get_u16();
                     let pos = buf.get_u16();
                     let nick_len = buf.get_u16();
@@ -107,14 +144,73 @@ pub(crate) async fn parse_elements(bot: &Arc<Bot>, record: &mut MessageRecord, e
             }
 
             AioElem::SrcMsg(src_msg) => {
-                result.push(CQCode::Reply(Reply {
-                    id: src_msg.orginal_seqs[0],
-                }));
-                is_front_reply = 2;
+                if let Some(seq) = src_msg.orginal_seqs.first() {
+                    // 回复的id使用OneBot的消息id，没有记录过的消息用SrcMsg里面的信息记录一个
+                    let msg_uid = src_msg.pb_reverse.as_ref().and_then(|v| v.msg_uid).unwrap_or_default();
+                    let message_id = MessageId::new(bot.unique_id, contact.clone(), *seq, msg_uid, src_msg.time.unwrap_or_default());
+                    let id = bot.save_message_id(message_id).await.id;
+                    result.push(CQCode::Reply(Reply {
+                        id: id as i64,
+                    }));
+                    is_front_reply = 2;
+                }
+            }
+
+            AioElem::MarketFace(face) => {
+                // 商城表情后面会跟一个[表情名]的文本
+                single_element = true;
+                result.push(parse_market_face(face));
+            }
+
+            AioElem::VideoFile(video) => {
+                if !result.iter().any(|v| matches!(v, CQCode::Video(_))) {
+                    result.push(CQCode::Video(Video {
+                        file: video.file_name.unwrap_or_else(|| hex::encode(video.file_uuid.unwrap_or_default())),
+                        url: None,
+                        cover: None,
+                    }))
+                }
             }
 
             AioElem::ArkJson(LightArk { data }) => {
-                warn!("Unsupported ArkJson")
+                single_element = true;
+                result.clear();
+                let Some(data) = decode_card_data(&data) else {
+                    warn!("Invalid ArkJson: {}", hex::encode(&data));
+                    continue;
+                };
+                result.push(parse_light_app(data));
+            }
+
+            AioElem::RichMsg(RichMsg { template1, service_id }) => {
+                single_element = true;
+                let Some(data) = template1.as_deref().and_then(decode_card_data) else {
+                    result.clear();
+                    warn!("Invalid RichMsg: {:?}", template1);
+                    continue;
+                };
+                let service_id = service_id.unwrap_or_default();
+                if service_id == 35 && parse_xml_attr(&data, "multiMsgFlag").as_deref() == Some("1") {
+                    if let Some(res_id) = parse_xml_attr(&data, "m_resid") {
+                        if let Some(elements) = expand_long_msg(bot, res_id, mode).await {
+                            replace_long_msg_body(result, elements);
+                            continue;
+                        }
+                    }
+                }
+                result.clear();
+                result.push(parse_rich_msg(data, service_id));
+            }
+
+            AioElem::TransElem(TransElem { elem_type: Some(24), elem_value: Some(value) }) => {
+                if let Some(info) = decode_group_file(value) {
+                    result.push(CQCode::File(File {
+                        id: info.file_id.unwrap_or_default(),
+                        name: info.file_name.unwrap_or_default(),
+                        size: info.file_size.unwrap_or_default(),
+                        busid: info.bus_id.unwrap_or_default(),
+                    }))
+                }
             }
 
             AioElem::CommonElem(CommonElem{ service_type, data, business_type }) => {
@@ -122,20 +218,33 @@ pub(crate) async fn parse_elements(bot: &Arc<Bot>, record: &mut MessageRecord, e
                 if service_type == 3 { // 闪照
                     error!("Unsupported service_type: 3")
                 } else if service_type == 33 { // 表情消息，扩展出来的
-                    let comm_face = CommonFaceElem::decode(data).unwrap();
+                    let Ok(comm_face) = CommonFaceElem::decode(data) else {
+                        warn!("Invalid CommonFaceElem, skip this!");
+                        continue;
+                    };
                     result.push(CQCode::Face(Face::new(
                         comm_face.face_id
                     )))
                 } else if service_type == 37 { // 大的表情消息
+                    let Ok(big_face) = CommonBigFaceElem::decode(data) else {
+                        warn!("Invalid CommonBigFaceElem, skip this!");
+                        continue;
+                    };
                     single_element = true;
                     result.clear();
-                    let big_face = CommonBigFaceElem::decode(data).unwrap();
-                    result.push(CQCode::Face(Face::new_big_face(
-                        big_face.face_id, big_face.result.map_or(0, |v| v.parse().unwrap_or(0))
-                    )))
+                    let face_result = big_face.result.map_or(0, |v| v.parse().unwrap_or(0));
+                    result.push(match big_face.face_id {
+                        DICE_FACE_ID => CQCode::NewDice(NewDice { id: face_result as i32 }),
+                        RPS_FACE_ID => CQCode::NewRPS(NewRPS { id: face_result as i32 }),
+                        BASKETBALL_FACE_ID => CQCode::Basketball(Basketball { id: face_result as i32 }),
+                        face_id => CQCode::Face(Face::new_big_face(face_id, face_result))
+                    })
                 } else if service_type == 48 { // 新版本专属的图片推送
-                    let msg_info = MsgInfo::decode(data).unwrap();
-                    parse_comm_elem_48(bot, result, business_type.unwrap(), msg_info).await;
+                    let (Ok(msg_info), Some(business_type)) = (MsgInfo::decode(data), business_type) else {
+                        warn!("Invalid CommonElem(48), skip this!");
+                        continue;
+                    };
+                    parse_comm_elem_48(&mut media_urls, result, business_type, msg_info);
                 } else {
                     warn!("Unsupported CommonElem: {}", service_type)
                 }
@@ -145,33 +254,303 @@ pub(crate) async fn parse_elements(bot: &Arc<Bot>, record: &mut MessageRecord, e
     }
 }
 
-pub async fn parse_comm_elem_48(bot: &Arc<Bot>, result: &mut Vec<CQCode>, business_type: u32, msg_info: MsgInfo) {
+/// 图片、视频、语音的下载链接都要单独请求，先把一条消息里面的一起并发请求，按file_uuid返回
+async fn resolve_media_urls(bot: &Arc<Bot>, contact: &Contact, elems: &[Elem]) -> HashMap<String, Result<String, Error>> {
+    let mut tasks = tokio::task::JoinSet::new();
+    for elem in elems {
+        let Some(AioElem::CommonElem(CommonElem { service_type: 48, data, .. })) = &elem.aio_elem else {
+            continue;
+        };
+        let Ok(msg_info) = MsgInfo::decode(data.as_slice()) else {
+            continue;
+        };
+        for msg in msg_info.msg_info_body {
+            let bot = Arc::clone(bot);
+            let contact = contact.clone();
+            tasks.spawn(async move {
+                let index = msg.index;
+                let url = match index.file_info.file_type.file_type {
+                    1 => {
+                        let picture = msg.picture?;
+                        bot.get_image_url(&contact, picture.domain, &picture.url_path).await
+                    }
+                    2 => bot.get_video_url(&contact, index.clone()).await,
+                    3 => bot.get_ptt_url(&contact, index.clone()).await,
+                    _ => return None,
+                };
+                Some((index.file_uuid, url))
+            });
+        }
+    }
+    let mut urls = HashMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(Some((file_uuid, url))) => { urls.insert(file_uuid, url); }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to resolve media url: {}", e),
+        }
+    }
+    urls
+}
+
+/// 离线解析时`media_urls`为空，这时候图片、视频、语音都没有下载链接
+pub fn parse_comm_elem_48(media_urls: &mut HashMap<String, Result<String, Error>>, result: &mut Vec<CQCode>, business_type: u32, msg_info: MsgInfo) {
     for msg in msg_info.msg_info_body.iter() {
         let index = &msg.index;
-        let upload_time = index.upload_time;
         let file_info = &index.file_info;
         let file_uuid = &index.file_uuid;
         let file_type = file_info.file_type.file_type;
+        let url = media_urls.remove(file_uuid).unwrap_or_else(|| Ok(String::new()));
         if file_type == 1 {
-            let picture = msg.picture.as_ref().unwrap();
-            let url_path = &picture.url_path;
-            let domain = picture.domain.as_ref()
-                .map_or("multimedia.nt.qq.com".to_string(), |v| v.clone());
-            let rkey = crate::service::rich_media::get_download_reky(&bot, business_type as u8).await;
             let sub_type = msg_info.ext_info.as_ref()
                 .map_or(0, |v| v.pic.as_ref()
                     .map_or(0, |pic| pic.biz_type.unwrap_or(0))
                 );
-            if let Ok(Some(rkey)) = rkey {
-                let url = format!("https://{}{}&spec=0{}", domain, url_path, rkey.key);
-                result.push(CQCode::Image(Image::with_sub_type(
-                    file_info.file_name.clone(), url, sub_type
-                )))
-            } else {
-                warn!("Failed to get download rkey for picture: {:?}", rkey);
+            let summary = msg_info.ext_info.as_ref()
+                .and_then(|v| v.pic.as_ref())
+                .and_then(|pic| pic.text_summary.clone())
+                .unwrap_or_default();
+            match url {
+                Ok(url) => result.push(CQCode::Image(Image {
+                    summary,
+                    ..Image::with_sub_type(file_info.file_name.clone(), url, sub_type)
+                })),
+                Err(e) => warn!("Failed to get download url for picture(business_type: {}): {}", business_type, e)
+            }
+        } else if file_type == 2 {
+            let url = url
+                .map_err(|e| warn!("Failed to get download url for video: {}", e))
+                .ok()
+                .filter(|url| !url.is_empty());
+            // 新版本的视频会同时带一个旧的VideoFile，优先使用这里带链接的
+            result.retain(|v| !matches!(v, CQCode::Video(_)));
+            result.push(CQCode::Video(Video {
+                file: file_uuid.clone(),
+                url,
+                cover: None,
+            }))
+        } else if file_type == 3 {
+            if !result.iter().any(|v| matches!(v, CQCode::Record(_))) {
+                let url = url
+                    .map_err(|e| warn!("Failed to get download url for ptt: {}", e))
+                    .ok()
+                    .filter(|url| !url.is_empty());
+                result.push(CQCode::Record(Record {
+                    file: file_uuid.clone(),
+                    url,
+                    magic: None,
+                }))
             }
         } else {
             warn!("Unsupported file type: {}", file_type)
         }
     }
-}
\ No newline at end of file
+}
+
+fn parse_market_face(face: MarketFace) -> CQCode {
+    let tab_id = face.tab_id.unwrap_or_default();
+    // 旧版的骰子和猜拳，结果在mobile_param的最后一位
+    let face_result = face.mobile_param.as_ref()
+        .and_then(|param| param.last())
+        .map_or(0, |c| (*c as char).to_digit(10).map_or(0, |v| v as i32 + 1));
+    match tab_id {
+        DICE_TAB_ID => CQCode::NewDice(NewDice { id: face_result }),
+        RPS_TAB_ID => CQCode::NewRPS(NewRPS { id: face_result }),
+        _ => CQCode::MFace(MFace {
+            emoji_id: hex::encode(face.face_id.unwrap_or_default()),
+            emoji_package_id: tab_id,
+            key: String::from_utf8_lossy(&face.key.unwrap_or_default()).to_string(),
+            summary: String::from_utf8_lossy(&face.face_name.unwrap_or_default()).to_string(),
+        })
+    }
+}
+
+/// 卡片消息第一个字节为1时后面的内容经过zlib压缩
+fn decode_card_data(data: &[u8]) -> Option<String> {
+    let (flag, content) = data.split_first()?;
+    let content = if *flag == 1 {
+        decompress_deflate(content).ok()?
+    } else {
+        content.to_vec()
+    };
+    String::from_utf8(content).ok()
+}
+
+fn parse_light_app(data: String) -> CQCode {
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&data) {
+        if json["app"] == "com.tencent.multimsg" {
+            if let Some(res_id) = json["meta"]["detail"]["resid"].as_str() {
+                return CQCode::Forward(Forward { id: res_id.to_string() });
+            }
+        }
+        if json["app"] == "com.tencent.map" {
+            let search = &json["meta"]["Location.Search"];
+            let coordinate = |name: &str| search[name].as_str()
+                .and_then(|v| v.parse::<f64>().ok())
+                .or_else(|| search[name].as_f64());
+            if let (Some(lat), Some(lon)) = (coordinate("lat"), coordinate("lng")) {
+                return CQCode::Location(Location {
+                    lat,
+                    lon,
+                    title: search["name"].as_str().map(|v| v.to_string()),
+                    content: search["address"].as_str().map(|v| v.to_string()),
+                });
+            }
+        }
+    }
+    CQCode::Json(Json { data })
+}
+
+/// 长消息的内容需要从长消息服务拉取，离线解析或者超过层数限制时不展开
+async fn expand_long_msg(bot: &Arc<Bot>, res_id: String, mode: ParseMode) -> Option<Vec<CQCode>> {
+    let ParseMode::Online { depth } = mode else {
+        return None;
+    };
+    if depth >= MAX_LONG_MSG_DEPTH {
+        warn!("Long msg is nested too deep, skip expanding: {}", res_id);
+        return None;
+    }
+    match Box::pin(bot.fetch_forward_msg(res_id, depth + 1)).await {
+        Ok(records) => Some(records.into_iter().flat_map(|record| record.elements).collect()),
+        Err(e) => {
+            warn!("Failed to expand long msg: {}", e);
+            None
+        }
+    }
+}
+
+/// 长消息里面是完整的消息内容，只替换掉已经解析出来的文本，保留回复这类元素
+fn replace_long_msg_body(result: &mut Vec<CQCode>, elements: Vec<CQCode>) {
+    result.retain(|element| !matches!(element, CQCode::Text(_)));
+    let has_reply = result.iter().any(|element| matches!(element, CQCode::Reply(_)));
+    result.extend(elements.into_iter()
+        .filter(|element| !(has_reply && matches!(element, CQCode::Reply(_)))));
+}
+
+fn parse_rich_msg(data: String, service_id: i32) -> CQCode {
+    if service_id == 35 {
+        if let Some(res_id) = parse_xml_attr(&data, "m_resid") {
+            return CQCode::Forward(Forward { id: res_id });
+        }
+    }
+    let url = parse_xml_attr(&data, "url").filter(|url| !url.is_empty());
+    match (service_id, url) {
+        (1, Some(url)) => CQCode::Share(Share {
+            url,
+            title: parse_xml_tag(&data, "title"),
+            content: parse_xml_tag(&data, "summary"),
+            image: parse_xml_elem_attr(&data, "picture", "cover").filter(|v| !v.is_empty()),
+            file: None,
+        }),
+        (2, Some(url)) => parse_music(&data, url),
+        _ => CQCode::Xml(Xml { data, service_id })
+    }
+}
+
+/// 发送时只带id的音乐卡片解析回music，其它的都当作自定义音乐
+fn parse_music(data: &str, url: String) -> CQCode {
+    let music = if let Some(id) = url.strip_prefix("https://i.y.qq.com/v8/playsong.html?songid=") {
+        id.parse::<i32>().ok().map(|id| ("qq", id))
+    } else if let Some(id) = url.strip_prefix("https://music.163.com/song/") {
+        id.trim_end_matches('/').parse::<i32>().ok().map(|id| ("163", id))
+    } else {
+        None
+    };
+    if let Some((music_type, id)) = music {
+        return CQCode::Music(Music {
+            music_type: music_type.to_string(),
+            id,
+        });
+    }
+    CQCode::CustomMusic(CustomMusic {
+        music_type: "custom".to_string(),
+        url,
+        audio: parse_xml_elem_attr(data, "audio", "src").unwrap_or_default(),
+        title: parse_xml_tag(data, "title").unwrap_or_default(),
+        singer: parse_xml_tag(data, "summary").unwrap_or_default(),
+        image: parse_xml_elem_attr(data, "audio", "cover").filter(|v| !v.is_empty()),
+    })
+}
+
+fn parse_xml_attr(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = xml[start..].find('"')? + start;
+    Some(unescape_xml(&xml[start..end]))
+}
+
+/// 某个标签上的属性，例如`<audio src="..." />`
+fn parse_xml_elem_attr(xml: &str, elem: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{} ", elem))?;
+    let end = xml[start..].find('>')? + start;
+    parse_xml_attr(&xml[start..end], name)
+}
+
+fn parse_xml_tag(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = xml[start..].find(&format!("</{}>", tag))? + start;
+    Some(unescape_xml(&xml[start..end]))
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 群文件TransElem(24)，格式为[u8][u16 len][GroupFileExtra]
+pub(crate) fn decode_group_file(elem_value: Vec<u8>) -> Option<GroupFileExtraInfo> {
+    let mut buf = Bytes::from(elem_value);
+    if buf.remaining() < 3 {
+        return None;
+    }
+    buf.advance(1);
+    let len = buf.get_u16() as usize;
+    if buf.remaining() < len {
+        return None;
+    }
+    GroupFileExtra::decode(buf.slice(..len)).ok()
+        .and_then(|extra| extra.inner)
+        .and_then(|inner| inner.info)
+}
+
+#[test]
+fn test_parse_xml_attr() {
+    let xml = r#"<msg serviceID="35" templateID="1" m_resid="abc/def==" m_fileName="123">"#;
+    assert_eq!(parse_xml_attr(xml, "m_resid"), Some("abc/def==".to_string()));
+    assert_eq!(parse_xml_attr(xml, "m_fileName"), Some("123".to_string()));
+    assert_eq!(parse_xml_attr(xml, "brief"), None);
+    assert_eq!(parse_xml_attr(xml, "resid"), None);
+}
+
+#[test]
+fn test_replace_long_msg_body() {
+    let mut result = vec![
+        CQCode::Reply(Reply { id: 1 }),
+        CQCode::Text("[长消息]".to_string()),
+    ];
+    replace_long_msg_body(&mut result, vec![
+        CQCode::Reply(Reply { id: 1 }),
+        CQCode::Text("完整的内容".to_string()),
+    ]);
+    assert_eq!(result.len(), 2);
+    assert!(matches!(&result[0], CQCode::Reply(Reply { id: 1 })));
+    assert!(matches!(&result[1], CQCode::Text(text) if text == "完整的内容"));
+}
+
+#[test]
+fn test_parse_rich_msg() {
+    let xml = r#"<msg serviceID="1" templateID="1" action="web" brief="[分享] A&amp;B" url="https://example.com/?a=1&amp;b=2"><item layout="2"><picture cover="https://example.com/a.png" w="0" h="0" /><title>A&amp;B</title><summary>内容</summary></item></msg>"#;
+    match parse_rich_msg(xml.to_string(), 1) {
+        CQCode::Share(share) => {
+            assert_eq!(share.url, "https://example.com/?a=1&b=2");
+            assert_eq!(share.title.as_deref(), Some("A&B"));
+            assert_eq!(share.content.as_deref(), Some("内容"));
+            assert_eq!(share.image.as_deref(), Some("https://example.com/a.png"));
+        }
+        cq => panic!("Unexpected segment: {}", cq)
+    }
+    let xml = r#"<msg serviceID="2" templateID="1" action="web" url="https://music.163.com/song/123/"><item layout="2"><audio cover="" src="" /><title>t</title></item></msg>"#;
+    assert!(matches!(parse_rich_msg(xml.to_string(), 2), CQCode::Music(Music { id: 123, .. })));
+}
@@ -1,22 +1,56 @@
 pub mod decoder;
 pub mod encoder;
 pub mod record;
+pub mod source;
 
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
 use chrono::{Local, NaiveDateTime};
 use log::{info, warn};
 use prost::Message as ProstMessage;
 use ntrim_tools::cqp::CQCode;
 use crate::bot::Bot;
-use crate::pb::msg::{Grp, olpush_routing_head};
+use crate::events::BotEvent;
+use crate::pb::msg::{Grp, olpush_routing_head, RichText, TransElem};
+use crate::pb::msg::elem::AioElem;
 use crate::pb::trpc::olpush::Message;
+use crate::service::msg::{MessageId, SendMsgResult};
+use crate::servlet::olpush::msg::decoder::ParseMode;
 pub use record::{ * };
 
+/// 单个群最多补齐的消息数量
+const MAX_CATCH_UP_COUNT: i64 = 200;
+const CATCH_UP_BATCH_SIZE: i64 = 30;
+
 pub(super) async fn on_group_msg(bot: Arc<Bot>, msg: Message) {
+    handle_group_msg(bot, msg, false).await;
+}
+
+async fn handle_group_msg(bot: Arc<Bot>, msg: Message, is_history: bool) {
+    let Some((mut record, rich_text)) = build_group_record(&bot, msg) else {
+        return;
+    };
+    record.is_history = is_history;
+
+    let group_file = rich_text.elems.iter().find_map(|elem| match &elem.aio_elem {
+        Some(AioElem::TransElem(TransElem { elem_type: Some(24), elem_value: Some(value) })) => Some(value.clone()),
+        _ => None
+    });
+    if let Some(elem_value) = group_file {
+        let Contact::Group(_, group_id) = record.contact else { unreachable!() };
+        super::notice::on_group_file_upload(bot, group_id, record.sender_id, record.sender_uid, elem_value, record.msg_time).await;
+        return;
+    }
+
+    on_message_record(bot, record, rich_text).await;
+}
+
+fn build_group_record(bot: &Arc<Bot>, msg: Message) -> Option<(MessageRecord, RichText)> {
     let msg_time = msg.content_head.msg_time as i64;
     let msg_seq = msg.content_head.msg_seq;
     let msg_uid = msg.content_head.msg_uid;
-    let (sender_uid, sender_uin) = (msg.routing_head.peer_uid.unwrap(), msg.routing_head.peer_id);
+    let (sender_uid, sender_uin) = (msg.routing_head.peer_uid.unwrap_or_default(), msg.routing_head.peer_id);
     let from_sub_appid = msg.routing_head.from_app_id;
     let platform = msg.routing_head.platform;
     let (group_id, sender_nick, group_name) = match msg.routing_head.contact {
@@ -27,16 +61,16 @@ pub(super) async fn on_group_msg(bot: Arc<Bot>, msg: Message) {
         ),
         _ => {
             warn!("Invalid routing_head, msg_seq: {}", msg_seq);
-            return;
+            return None;
         }
     };
 
-    if msg.msg_body.rich_text.is_none() {
+    let Some(rich_text) = msg.msg_body.rich_text else {
         warn!("Empty rich_text, msg_seq: {}", msg_seq);
-        return;
-    }
+        return None;
+    };
 
-    let mut record = MessageRecord {
+    let record = MessageRecord {
         contact: Contact::Group(group_name, group_id),
         sender_id: sender_uin,
         sender_uid,
@@ -45,25 +79,184 @@ pub(super) async fn on_group_msg(bot: Arc<Bot>, msg: Message) {
         msg_time,
         msg_seq,
         msg_uid,
+        is_self: sender_uin == bot.unique_id,
+        is_history: false,
+        elements: Vec::new(),
+    };
+    Some((record, rich_text))
+}
+
+/// 补齐掉线期间错过的群消息，`from_seq`和`to_seq`都包括在内
+///
+/// 每补齐一批就把这批的最后一个seq写回数据库，失败时返回false，已经补齐的部分不会丢失，
+/// 补齐完成之前实时消息不会更新这个群的seq，下次同步的时候会从断开的地方继续
+pub(crate) async fn catch_up_group_msg(bot: Arc<Bot>, group_id: i64, from_seq: i64, to_seq: i64) -> bool {
+    let from_seq = from_seq.max(to_seq - MAX_CATCH_UP_COUNT + 1);
+    info!("Catch up group msg, group: {}, seq: {}..={}", group_id, from_seq, to_seq);
+    mark_catching_up(&bot, group_id);
+    let mut start_seq = from_seq;
+    while start_seq <= to_seq {
+        let end_seq = (start_seq + CATCH_UP_BATCH_SIZE - 1).min(to_seq);
+        let mut messages = match bot.get_group_raw_msg(group_id, start_seq, end_seq).await {
+            Ok(messages) => messages,
+            Err(e) => {
+                warn!("Failed to catch up group msg, group: {}, err: {:?}", group_id, e);
+                return false;
+            }
+        };
+        messages.sort_by_key(|msg| msg.content_head.msg_seq);
+        let mut msg_time = None;
+        for msg in messages {
+            msg_time = Some(msg.content_head.msg_time);
+            if super::dedup::is_duplicate_msg(msg.content_head.msg_uid) {
+                continue;
+            }
+            handle_group_msg(Arc::clone(&bot), msg, true).await;
+        }
+        update_group_seq(group_id, end_seq, msg_time).await;
+        start_seq = end_seq + 1;
+    }
+    CATCHING_UP_GROUPS.lock().unwrap().remove(&(bot.unique_id, group_id));
+    true
+}
+
+/// 正在补齐消息的群，(bot, group_id)
+static CATCHING_UP_GROUPS: Lazy<Mutex<HashSet<(i64, i64)>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 补齐完成之前实时消息不更新这个群的seq
+pub(crate) fn mark_catching_up(bot: &Arc<Bot>, group_id: i64) {
+    CATCHING_UP_GROUPS.lock().unwrap().insert((bot.unique_id, group_id));
+}
+
+/// 记录这个群已经处理到的seq，`msg_time`为None时不更新最后一条消息的时间
+async fn update_group_seq(group_id: i64, msg_seq: i64, msg_time: Option<i64>) {
+    #[cfg(feature = "sql")]
+    if crate::db::is_initialized() {
+        let pool = crate::db::PG_POOL.get().unwrap();
+        let msg_time = msg_time.and_then(|t| chrono::DateTime::from_timestamp(t, 0)).map(|t| t.naive_utc());
+        if let Err(e) = crate::db::SimpleMessageRecord::update_seq(pool, group_id, msg_seq, msg_time).await {
+            warn!("Failed to update group_simple_record: {:?}", e);
+        }
+    }
+}
+
+/// msg_type: 166，包括自己在其它设备上发送的同步消息
+pub(super) async fn on_friend_msg(bot: Arc<Bot>, msg: Message) {
+    if let Some((record, rich_text)) = build_friend_record(&bot, msg) {
+        on_message_record(bot, record, rich_text).await;
+    }
+}
+
+fn build_friend_record(bot: &Arc<Bot>, msg: Message) -> Option<(MessageRecord, RichText)> {
+    let msg_seq = msg.content_head.msg_seq;
+    let routing_head = msg.routing_head;
+    let is_self = routing_head.peer_id == bot.unique_id;
+    let friend_name = match routing_head.contact {
+        Some(olpush_routing_head::Contact::C2c(c2c)) => c2c.friend_name.unwrap_or_default(),
+        _ => "".to_string()
+    };
+    let sender_uid = routing_head.peer_uid.unwrap_or_default();
+    let (friend_uin, friend_uid) = if is_self {
+        (routing_head.receiver_id.unwrap_or_default() as i64, routing_head.receiver_uid.unwrap_or_default())
+    } else {
+        (routing_head.peer_id, sender_uid.clone())
+    };
+
+    let Some(rich_text) = msg.msg_body.rich_text else {
+        warn!("Empty rich_text, msg_seq: {}", msg_seq);
+        return None;
+    };
+
+    let record = MessageRecord {
+        contact: Contact::Friend(friend_name.clone(), friend_uin, friend_uid),
+        sender_id: routing_head.peer_id,
+        sender_uid,
+        sender_nick: if is_self { "".to_string() } else { friend_name },
+        sender_unique_title: "".to_string(),
+        msg_time: msg.content_head.msg_time,
+        msg_seq,
+        msg_uid: msg.content_head.msg_uid,
+        is_self,
+        is_history: false,
         elements: Vec::new(),
     };
+    Some((record, rich_text))
+}
 
-    let mut rich_text = msg.msg_body.rich_text.unwrap();
+/// 解析拉取到的历史消息，保存到数据库但是不推送事件
+pub(crate) async fn decode_history_msg(bot: &Arc<Bot>, msg: Message) -> Option<MessageRecord> {
+    let (mut record, rich_text) = match msg.routing_head.contact {
+        Some(olpush_routing_head::Contact::Grp(_)) => build_group_record(bot, msg)?,
+        _ => build_friend_record(bot, msg)?,
+    };
+    record.is_history = true;
+    save_record(bot, &record, &rich_text).await;
+    decoder::parse_elements(bot, &mut record, rich_text.elems, ParseMode::Online { depth: 0 }).await;
+    source::cache_msg_source(&record);
+    bot.save_message_id(MessageId::from_record(bot.unique_id, &record)).await;
+    Some(record)
+}
+
+/// 解析合并转发里面的消息，不保存也不缓存，`depth`为所在长消息的层数
+pub(crate) async fn decode_forward_msg(bot: &Arc<Bot>, msg: Message, depth: u32) -> Option<MessageRecord> {
+    let (mut record, rich_text) = match msg.routing_head.contact {
+        Some(olpush_routing_head::Contact::Grp(_)) => build_group_record(bot, msg)?,
+        _ => build_friend_record(bot, msg)?,
+    };
+    record.is_history = true;
+    decoder::parse_elements(bot, &mut record, rich_text.elems, ParseMode::Online { depth }).await;
+    Some(record)
+}
 
+/// 记录自己发出去的消息，和收到的消息存在同一张表里，msg_seq使用服务器分配的seq
+pub(crate) async fn on_sent_msg(bot: Arc<Bot>, contact: Contact, result: SendMsgResult, rich_text: RichText) {
+    let sender_uid = bot.client.session.read().await.uid.clone();
+    let mut record = MessageRecord {
+        contact,
+        sender_id: bot.unique_id,
+        sender_uid,
+        sender_nick: "".to_string(),
+        sender_unique_title: "".to_string(),
+        msg_time: result.msg_time,
+        msg_seq: result.msg_seq,
+        msg_uid: result.msg_uid,
+        is_self: true,
+        is_history: false,
+        elements: Vec::new(),
+    };
+    save_record(&bot, &record, &rich_text).await;
+    decoder::parse_elements(&bot, &mut record, rich_text.elems, ParseMode::Offline).await;
+    source::cache_msg_source(&record);
+}
+
+async fn save_record(bot: &Arc<Bot>, record: &MessageRecord, rich_text: &RichText) {
     #[cfg(feature = "sql")]
     if crate::db::is_initialized() {
         let pool = crate::db::PG_POOL.get().unwrap();
-        MessageRecord::insert(pool, &bot, &record, rich_text.encode_to_vec()).await.map_err(|e| {
+        if let Err(e) = MessageRecord::insert(pool, bot, record, rich_text.encode_to_vec()).await {
             warn!("Failed to insert message to pgsql: {:?}", e);
-        }).unwrap();
+        }
     }
+    // 补齐中的群由补齐流程更新seq，不然中间没有补齐的部分会被跳过
+    if let Contact::Group(_, group_id) = record.contact {
+        if !record.is_history && !CATCHING_UP_GROUPS.lock().unwrap().contains(&(bot.unique_id, group_id)) {
+            update_group_seq(group_id, record.msg_seq, Some(record.msg_time)).await;
+        }
+    }
+}
+
+async fn on_message_record(bot: Arc<Bot>, mut record: MessageRecord, rich_text: RichText) {
+    save_record(&bot, &record, &rich_text).await;
 
-    decoder::parse_elements(&bot, &mut record, rich_text.elems).await;
+    decoder::parse_elements(&bot, &mut record, rich_text.elems, ParseMode::Online { depth: 0 }).await;
+    source::cache_msg_source(&record);
+    bot.save_message_id(MessageId::from_record(bot.unique_id, &record)).await;
 
-    if std::env::var("PING_PONG").unwrap_or("1".to_string()) == "1" && record.to_raw_msg() == "ping" {
+    if !record.is_history && std::env::var("PING_PONG").unwrap_or("1".to_string()) == "1" && record.to_raw_msg() == "ping" {
         let result = Bot::send_msg(&bot, record.contact.clone(), vec![CQCode::Text("qqbot.rs -> pong".to_string())]).await;
         info!("Ping pong result: {:?}", result);
     }
 
     println!("{}", record);
+    bot.post_event(BotEvent::Message(Arc::new(record)));
 }
\ No newline at end of file
@@ -9,6 +9,7 @@ pub enum Contact {
     Stranger(String, i64, String),
 }
 
+#[derive(Debug)]
 pub struct MessageRecord {
     pub contact: Contact,
     pub sender_id: i64,
@@ -18,6 +19,10 @@ pub struct MessageRecord {
     pub msg_time: i64,
     pub msg_seq: i64,
     pub msg_uid: i64,
+    /// 自己在其它设备上发送的同步消息
+    pub is_self: bool,
+    /// 补齐或者拉取到的历史消息，不会触发ping pong这类实时处理
+    pub is_history: bool,
     pub elements: Vec<CQCode>,
 }
 
@@ -29,7 +34,8 @@ impl Display for MessageRecord {
             Contact::Stranger(user_name, uin, _) => ("陌生人", user_name, uin),
         };
         let raw_msg = self.to_raw_msg();
-        write!(f, "{}消息 [{}({})] {}({}): {}", contact.0, contact.1, contact.2, self.sender_nick, self.sender_uid, raw_msg)
+        let direction = if self.is_self { "发送" } else { "" };
+        write!(f, "{}{}消息 [{}({})] {}({}): {}", direction, contact.0, contact.1, contact.2, self.sender_nick, self.sender_uid, raw_msg)
     }
 }
 
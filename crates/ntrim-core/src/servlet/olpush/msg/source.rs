@@ -0,0 +1,101 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+use ntrim_tools::cqp::CQCode;
+use crate::bot::Bot;
+use crate::service::msg::message_id::contact_key;
+use crate::servlet::olpush::msg::{Contact, MessageRecord};
+
+/// 内存中最多缓存的消息数量，没有数据库的时候回复消息靠这个
+const MAX_CACHED_SOURCE: usize = 4096;
+
+/// 构建回复消息需要的原消息信息
+#[derive(Debug, Clone)]
+pub struct MsgSource {
+    pub msg_seq: i64,
+    pub msg_uid: i64,
+    pub msg_time: i64,
+    pub sender_id: i64,
+    pub sender_uid: String,
+    pub sender_nick: String,
+    pub summary: String,
+}
+
+impl MsgSource {
+    fn from_record(record: &MessageRecord) -> Self {
+        Self {
+            msg_seq: record.msg_seq,
+            msg_uid: record.msg_uid,
+            msg_time: record.msg_time,
+            sender_id: record.sender_id,
+            sender_uid: record.sender_uid.clone(),
+            sender_nick: record.sender_nick.clone(),
+            summary: summary_of(&record.elements),
+        }
+    }
+}
+
+//             contact_type contact_uin msg_seq
+type SourceKey = (&'static str, i64, i64);
+
+#[derive(Default)]
+struct SourceCache {
+    map: HashMap<SourceKey, MsgSource>,
+    queue: VecDeque<SourceKey>,
+}
+
+static SOURCE_CACHE: Lazy<Mutex<SourceCache>> = Lazy::new(|| Mutex::new(SourceCache::default()));
+
+fn source_key(contact: &Contact, msg_seq: i64) -> SourceKey {
+    let (contact_type, contact_uin) = contact_key(contact);
+    (contact_type, contact_uin, msg_seq)
+}
+
+pub(crate) fn cache_msg_source(record: &MessageRecord) {
+    let key = source_key(&record.contact, record.msg_seq);
+    let mut cache = SOURCE_CACHE.lock().unwrap();
+    if cache.map.insert(key, MsgSource::from_record(record)).is_none() {
+        cache.queue.push_back(key);
+        if cache.queue.len() > MAX_CACHED_SOURCE {
+            if let Some(old) = cache.queue.pop_front() {
+                cache.map.remove(&old);
+            }
+        }
+    }
+}
+
+/// 先查内存缓存，再查数据库
+pub(crate) async fn find_msg_source(bot: &Arc<Bot>, contact: &Contact, msg_seq: i64) -> Option<MsgSource> {
+    let key = source_key(contact, msg_seq);
+    if let Some(source) = SOURCE_CACHE.lock().unwrap().map.get(&key) {
+        return Some(source.clone());
+    }
+    #[cfg(feature = "sql")]
+    if crate::db::is_initialized() {
+        let pool = crate::db::PG_POOL.get().unwrap();
+        match MessageRecord::get_messages_by_seq(pool, bot, key.0, key.1, msg_seq, msg_seq).await {
+            Ok(records) => return records.first().map(MsgSource::from_record),
+            Err(e) => log::warn!("Failed to query msg source from pgsql: {:?}", e),
+        }
+    }
+    None
+}
+
+/// 回复消息里面带的原消息摘要
+pub(crate) fn summary_of(elements: &[CQCode]) -> String {
+    elements.iter().map(|element| match element {
+        CQCode::Text(text) => text.clone(),
+        CQCode::At(at) => format!("@{}", at.qq),
+        CQCode::Face(_) | CQCode::MFace(_) => "[表情]".to_string(),
+        CQCode::Image(_) => "[图片]".to_string(),
+        CQCode::Record(_) => "[语音]".to_string(),
+        CQCode::Video(_) => "[视频]".to_string(),
+        CQCode::File(_) => "[文件]".to_string(),
+        CQCode::Forward(_) => "[聊天记录]".to_string(),
+        CQCode::Share(_) => "[分享]".to_string(),
+        CQCode::Music(_) | CQCode::CustomMusic(_) => "[音乐]".to_string(),
+        CQCode::Location(_) => "[位置]".to_string(),
+        CQCode::Reply(_) => "".to_string(),
+        _ => "[消息]".to_string(),
+    }).collect()
+}
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use bytes::{Buf, Bytes};
+use log::{debug, warn};
+use prost::Message as ProstMessage;
+use crate::bot::Bot;
+use crate::events::{BotEvent, GroupHonor, NoticeEvent, RequestEvent};
+use crate::pb::msg::{olpush_routing_head, FileExtra};
+use crate::pb::onlinepush::GeneralGrayTipInfo;
+use crate::servlet::olpush::msg::decoder::decode_group_file;
+use crate::pb::trpc::olpush::{FriendRecall, FriendRequest, GroupAdmin, GroupMute, Message, NotifyMessageBody};
+#[cfg(feature = "sql")]
+use crate::commands::troop::{GroupMemberInfo, GroupMemberPermission};
+#[cfg(feature = "sql")]
+use crate::servlet::olpush::msg::MessageRecord;
+
+pub(super) async fn on_friend_request_add(bot: Arc<Bot>, msg: Message) {
+    let uin = msg.routing_head.peer_id;
+    let request = msg.msg_body.msg_content
+        .and_then(|content| FriendRequest::decode(content.as_slice()).ok())
+        .and_then(|req| req.info)
+        .unwrap_or_default();
+    let nick = match msg.routing_head.contact {
+        Some(olpush_routing_head::Contact::C2c(c2c)) => c2c.friend_name.unwrap_or_default(),
+        _ => "".to_string()
+    };
+    // 推送里面没有处理申请需要的seq，处理申请的时候再从系统消息里面找
+    bot.post_event(BotEvent::Request(RequestEvent::FriendRequest {
+        uin,
+        uid: request.source_uid
+            .or(msg.routing_head.peer_uid)
+            .unwrap_or_default(),
+        nick,
+        comment: request.message.unwrap_or_default(),
+        source: request.source.unwrap_or_default(),
+        flag: uin.to_string(),
+        time: msg.content_head.msg_time,
+    }));
+}
+
+pub(super) async fn on_unidirectional_friend_increase(bot: Arc<Bot>, msg: Message) {
+    let nick = match msg.routing_head.contact {
+        Some(olpush_routing_head::Contact::C2c(c2c)) => c2c.friend_name.unwrap_or_default(),
+        _ => "".to_string()
+    };
+    bot.post_event(BotEvent::Notice(NoticeEvent::FriendAdd {
+        uin: msg.routing_head.peer_id,
+        uid: msg.routing_head.peer_uid.unwrap_or_default(),
+        nick,
+        time: msg.content_head.msg_time,
+    }));
+}
+
+/// msg_type: 528
+pub(super) async fn on_c2c_notice(bot: Arc<Bot>, msg: Message) {
+    let sub_type = msg.content_head.sub_type.unwrap_or_default();
+    let Some(content) = msg.msg_body.msg_content else {
+        warn!("Empty c2c notice content, sub_type: {}", sub_type);
+        return;
+    };
+    match sub_type {
+        138 => on_friend_recall(bot, content).await,
+        290 => match GeneralGrayTipInfo::decode(content.as_slice()) {
+            Ok(tip) => on_general_gray_tip(&bot, 0, tip, msg.content_head.msg_time).await,
+            Err(e) => warn!("Failed to decode friend gray tip: {:?}", e)
+        },
+        _ => debug!("Unknown c2c notice sub_type: {}, content: {}", sub_type, hex::encode(&content))
+    }
+}
+
+/// msg_type: 529
+pub(super) async fn on_offline_file(bot: Arc<Bot>, msg: Message) {
+    let sub_type = msg.content_head.sub_type.unwrap_or_default();
+    if sub_type != 4 {
+        debug!("Unknown offline file sub_type: {}", sub_type);
+        return;
+    }
+    let Some(file) = msg.msg_body.msg_content
+        .and_then(|content| FileExtra::decode(content.as_slice()).ok())
+        .and_then(|extra| extra.file) else {
+        warn!("Failed to decode offline file");
+        return;
+    };
+    bot.post_event(BotEvent::Notice(NoticeEvent::OfflineFile {
+        uin: msg.routing_head.peer_id,
+        uid: msg.routing_head.peer_uid.unwrap_or_default(),
+        file_id: file.file_uuid.unwrap_or_default(),
+        file_hash: file.file_hash.unwrap_or_default(),
+        name: file.file_name.unwrap_or_default(),
+        size: file.file_size.unwrap_or_default(),
+        time: msg.content_head.msg_time,
+    }));
+}
+
+/// 群文件以`TransElem(24)`的形式出现在群消息里面
+pub(super) async fn on_group_file_upload(bot: Arc<Bot>, group_id: i64, uin: i64, uid: String, elem_value: Vec<u8>, time: i64) {
+    let Some(info) = decode_group_file(elem_value) else {
+        warn!("Failed to decode group file extra");
+        return;
+    };
+    bot.post_event(BotEvent::Notice(NoticeEvent::GroupFileUpload {
+        group_id,
+        uin,
+        uid,
+        file_id: info.file_id.unwrap_or_default(),
+        name: info.file_name.unwrap_or_default(),
+        size: info.file_size.unwrap_or_default(),
+        busid: info.bus_id.unwrap_or_default(),
+        time,
+    }));
+}
+
+/// msg_type: 732
+pub(super) async fn on_group_notice(bot: Arc<Bot>, msg: Message) {
+    let sub_type = msg.content_head.sub_type.unwrap_or_default();
+    let time = msg.content_head.msg_time;
+    let Some(content) = msg.msg_body.msg_content else {
+        warn!("Empty group notice content, sub_type: {}", sub_type);
+        return;
+    };
+    if sub_type == 12 {
+        on_group_mute(bot, content, time).await;
+        return;
+    }
+    let Some((group_id, body)) = decode_group_notify(&content) else {
+        warn!("Failed to decode group notice, sub_type: {}, content: {}", sub_type, hex::encode(&content));
+        return;
+    };
+    match sub_type {
+        16 if body.field13 == Some(35) => on_group_reaction(bot, group_id, body, time).await,
+        17 => on_group_recall(bot, group_id, body).await,
+        _ => if let Some(tip) = body.general_gray_tip {
+            on_general_gray_tip(&bot, group_id, tip, time).await;
+        } else {
+            debug!("Unknown group notice sub_type: {}, content: {}", sub_type, hex::encode(&content))
+        }
+    }
+}
+
+/// msg_type: 44
+pub(super) async fn on_group_admin_change(bot: Arc<Bot>, msg: Message) {
+    let Some(admin) = msg.msg_body.msg_content
+        .and_then(|content| GroupAdmin::decode(content.as_slice()).ok()) else {
+        warn!("Failed to decode group admin change");
+        return;
+    };
+    let group_id = admin.group_uin.unwrap_or_default();
+    let body = admin.body.unwrap_or_default();
+    let (uid, is_admin) = match (body.extra_enable, body.extra_disable) {
+        (Some(extra), _) => (extra.admin_uid.unwrap_or_default(), true),
+        (None, Some(extra)) => (extra.admin_uid.unwrap_or_default(), false),
+        (None, None) => return,
+    };
+    let uin = bot.get_troop_member_uin(group_id, &uid).await.unwrap_or_default();
+    post_group_notice(&bot, NoticeEvent::GroupAdminChange {
+        group_id,
+        uin,
+        uid,
+        is_admin,
+        time: msg.content_head.msg_time,
+    }).await;
+}
+
+async fn on_group_mute(bot: Arc<Bot>, content: Vec<u8>, time: i64) {
+    let Ok(mute) = GroupMute::decode(content.as_slice()) else {
+        warn!("Failed to decode group mute: {}", hex::encode(&content));
+        return;
+    };
+    let group_id = mute.group_uin.unwrap_or_default();
+    let operator_uid = mute.operator_uid.unwrap_or_default();
+    let operator_uin = bot.get_troop_member_uin(group_id, &operator_uid).await.unwrap_or_default();
+    let state = mute.data.and_then(|data| data.state).unwrap_or_default();
+    let target_uid = state.target_uid.unwrap_or_default();
+    let target_uin = if target_uid.is_empty() {
+        0
+    } else {
+        bot.get_troop_member_uin(group_id, &target_uid).await.unwrap_or_default()
+    };
+    post_group_notice(&bot, NoticeEvent::GroupMute {
+        group_id,
+        operator_uin,
+        operator_uid,
+        target_uin,
+        target_uid,
+        duration: state.duration.unwrap_or_default() as i64,
+        time,
+    }).await;
+}
+
+/// 群通知的格式：群号(4) + 未知(1) + 长度(2) + NotifyMessageBody
+fn decode_group_notify(content: &[u8]) -> Option<(i64, NotifyMessageBody)> {
+    let mut buf = Bytes::copy_from_slice(content);
+    if buf.remaining() < 7 {
+        return None;
+    }
+    let group_id = buf.get_u32() as i64;
+    buf.advance(1);
+    let len = (buf.get_u16() as usize).min(buf.remaining());
+    let body = NotifyMessageBody::decode(buf.slice(..len)).ok()?;
+    Some((group_id, body))
+}
+
+async fn on_group_recall(bot: Arc<Bot>, group_id: i64, body: NotifyMessageBody) {
+    let Some(recall) = body.recall else {
+        return;
+    };
+    let operator_uid = recall.operator_uid.unwrap_or_default();
+    let operator_uin = bot.get_troop_member_uin(group_id, &operator_uid).await.unwrap_or_default();
+    for msg in recall.recall_messages {
+        let sender_uid = msg.author_uid.unwrap_or_default();
+        let sender_uin = if sender_uid == operator_uid {
+            operator_uin
+        } else {
+            bot.get_troop_member_uin(group_id, &sender_uid).await.unwrap_or_default()
+        };
+        let msg_seq = msg.seq.unwrap_or_default();
+        let msg_uid = mark_group_msg_recalled(&bot, group_id, msg_seq).await;
+        bot.post_event(BotEvent::Notice(NoticeEvent::GroupRecall {
+            group_id,
+            operator_uin,
+            operator_uid: operator_uid.clone(),
+            sender_uin,
+            sender_uid,
+            msg_seq,
+            msg_uid,
+            time: msg.time.unwrap_or_default(),
+        }));
+    }
+}
+
+async fn on_group_reaction(bot: Arc<Bot>, group_id: i64, body: NotifyMessageBody, time: i64) {
+    let Some(info) = body.reaction.and_then(|r| r.body).and_then(|b| b.info) else {
+        warn!("Failed to decode group reaction");
+        return;
+    };
+    let data = info.data.unwrap_or_default();
+    let operator_uid = data.operator_uid.unwrap_or_default();
+    let operator_uin = bot.get_troop_member_uin(group_id, &operator_uid).await.unwrap_or_default();
+    bot.post_event(BotEvent::Notice(NoticeEvent::GroupReaction {
+        group_id,
+        operator_uin,
+        operator_uid,
+        msg_seq: info.target.and_then(|t| t.msg_seq).unwrap_or_default(),
+        emoji_id: data.code.unwrap_or_default(),
+        count: data.count.unwrap_or_default(),
+        is_add: data.r#type != Some(2),
+        time,
+    }));
+}
+
+async fn on_friend_recall(bot: Arc<Bot>, content: Vec<u8>) {
+    let Some(info) = FriendRecall::decode(content.as_slice()).ok().and_then(|r| r.info) else {
+        warn!("Failed to decode friend recall: {}", hex::encode(&content));
+        return;
+    };
+    let from_uid = info.from_uid.unwrap_or_default();
+    let operator_uin = bot.get_friend_uin(&from_uid).await.unwrap_or_default();
+    // 其它设备撤回自己发送的消息时，from_uid是自己
+    let (uin, uid) = if operator_uin == bot.unique_id {
+        let to_uid = info.to_uid.unwrap_or_default();
+        (bot.get_friend_uin(&to_uid).await.unwrap_or_default(), to_uid)
+    } else {
+        (operator_uin, from_uid)
+    };
+    let msg_uid = info.msg_uid.unwrap_or_default();
+    mark_msg_recalled(&bot, msg_uid).await;
+    bot.post_event(BotEvent::Notice(NoticeEvent::FriendRecall {
+        uin,
+        uid,
+        operator_uin,
+        msg_seq: info.seq.unwrap_or_default(),
+        msg_uid,
+        time: info.time.unwrap_or_default(),
+    }));
+}
+
+/// 在数据库中标记群消息已撤回，返回消息的msg_uid，未知时为0
+pub(super) async fn mark_group_msg_recalled(bot: &Arc<Bot>, group_id: i64, msg_seq: i64) -> i64 {
+    #[cfg(feature = "sql")]
+    if crate::db::is_initialized() {
+        let pool = crate::db::PG_POOL.get().unwrap();
+        match MessageRecord::set_group_msg_recalled(pool, bot, group_id, msg_seq).await {
+            Ok(msg_uid) => return msg_uid.unwrap_or_default(),
+            Err(e) => warn!("Failed to mark group msg as recalled: {:?}", e)
+        }
+    }
+    0
+}
+
+pub(super) async fn mark_msg_recalled(bot: &Arc<Bot>, msg_uid: i64) {
+    #[cfg(feature = "sql")]
+    if crate::db::is_initialized() {
+        let pool = crate::db::PG_POOL.get().unwrap();
+        if let Err(e) = MessageRecord::set_msg_recalled(pool, bot, msg_uid).await {
+            warn!("Failed to mark msg as recalled: {:?}", e);
+        }
+    }
+}
+
+/// 灰条提示，`group_id`为0时为好友灰条
+pub(super) async fn on_general_gray_tip(bot: &Arc<Bot>, group_id: i64, tip: GeneralGrayTipInfo, time: i64) {
+    let params: HashMap<String, String> = tip.msg_templ_param.into_iter()
+        .map(|p| (p.name.unwrap_or_default(), p.value.unwrap_or_default()))
+        .collect();
+    let param_uin = |name: &str| params.get(name)
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or_default();
+    // 戳一戳：uin_str1 戳了戳 uin_str2
+    if tip.busi_type == Some(12) && params.contains_key("uin_str1") {
+        let sender_uin = param_uin("uin_str1");
+        let mut target_uin = param_uin("uin_str2");
+        if target_uin == 0 {
+            target_uin = bot.unique_id;
+        }
+        let action = params.get("action_str")
+            .or_else(|| params.get("alt_str1"))
+            .cloned()
+            .unwrap_or_default();
+        bot.post_event(BotEvent::Notice(NoticeEvent::Poke {
+            group_id,
+            sender_uin,
+            target_uin,
+            action,
+            suffix: params.get("suffix_str").cloned().unwrap_or_default(),
+            time,
+        }));
+        return;
+    }
+    let templ_id = tip.templ_id.unwrap_or_default();
+    let honor = match templ_id {
+        1052 => Some(GroupHonor::Performer),
+        1053 | 1054 => Some(GroupHonor::Talkative),
+        1067 => Some(GroupHonor::Emotion),
+        _ => None
+    };
+    if let Some(honor) = honor {
+        bot.post_event(BotEvent::Notice(NoticeEvent::GroupHonorChange {
+            group_id,
+            uin: param_uin("uin"),
+            honor,
+            time,
+        }));
+        return;
+    }
+    let mut content = tip.content.unwrap_or_default();
+    for (name, value) in params.iter() {
+        content = content.replace(&format!("{{{}}}", name), value);
+    }
+    on_gray_tip_content(bot, group_id, templ_id, content, time).await;
+}
+
+/// 纯文本灰条，头衔变更也是通过灰条下发的
+pub(super) async fn on_gray_tip_content(bot: &Arc<Bot>, group_id: i64, templ_id: u64, content: String, time: i64) {
+    if let Some((uin, special_title)) = parse_special_title(&content) {
+        post_group_notice(bot, NoticeEvent::GroupSpecialTitleChange {
+            group_id,
+            uin,
+            special_title,
+            time,
+        }).await;
+        return;
+    }
+    bot.post_event(BotEvent::Notice(NoticeEvent::GroupGrayTip {
+        group_id,
+        templ_id,
+        content,
+        time,
+    }));
+}
+
+/// 例如：`<{"cmd":5,"data":"10001","text":"昵称"}>获得群主授予的<{"cmd":1,"data":"...","text":"头衔"}>头衔`
+fn parse_special_title(content: &str) -> Option<(i64, String)> {
+    if !content.contains("获得群主授予的") {
+        return None;
+    }
+    let (mut uin, mut title) = (None, None);
+    for segment in content.split('<').skip(1) {
+        let Some((segment, _)) = segment.split_once('>') else {
+            continue;
+        };
+        let Ok(segment) = serde_json::from_str::<serde_json::Value>(segment) else {
+            continue;
+        };
+        match segment["cmd"].as_i64() {
+            Some(5) => uin = segment["data"].as_str().and_then(|v| v.parse::<i64>().ok()),
+            Some(1) => title = segment["text"].as_str().map(|v| v.to_string()),
+            _ => {}
+        }
+    }
+    Some((uin?, title?))
+}
+
+/// 推送群通知，同时更新群成员缓存
+pub(super) async fn post_group_notice(bot: &Arc<Bot>, notice: NoticeEvent) {
+    #[cfg(feature = "sql")]
+    if crate::db::is_initialized() {
+        let pool = crate::db::PG_POOL.get().unwrap();
+        let result = match &notice {
+            NoticeEvent::GroupMute { group_id, target_uin, duration, time, .. } if *target_uin != 0 => {
+                let shut_up_timestamp = if *duration == 0 { 0 } else { time + duration };
+                GroupMemberInfo::update_shut_up_timestamp(pool, *group_id, *target_uin, shut_up_timestamp).await
+            }
+            NoticeEvent::GroupAdminChange { group_id, uin, is_admin, .. } => {
+                let permission = if *is_admin {
+                    GroupMemberPermission::Administrator
+                } else {
+                    GroupMemberPermission::Member
+                };
+                GroupMemberInfo::update_permission(pool, *group_id, *uin, permission).await
+            }
+            NoticeEvent::GroupSpecialTitleChange { group_id, uin, special_title, .. } => {
+                GroupMemberInfo::update_special_title(pool, *group_id, *uin, special_title).await
+            }
+            _ => Ok(())
+        };
+        if let Err(e) = result {
+            warn!("Failed to update group member cache: {:?}", e);
+        }
+    }
+    bot.post_event(BotEvent::Notice(notice));
+}
+
+#[test]
+fn test_parse_special_title() {
+    let content = r#"<{"cmd":5,"data":"10001","text":"伏秋洛"}>获得群主授予的<{"cmd":1,"data":"https://qun.qq.com","text":"小可爱"}>头衔"#;
+    assert_eq!(parse_special_title(content), Some((10001, "小可爱".to_string())));
+    assert_eq!(parse_special_title("伏秋洛 撤回了一条消息"), None);
+}
@@ -0,0 +1,156 @@
+use std::sync::Arc;
+use bytes::{Buf, Bytes};
+use jcers::Jce;
+use log::{debug, warn};
+use prost::Message;
+use crate::bot::Bot;
+use crate::events::{BotEvent, NoticeEvent};
+use crate::jce::onlinepush::reqpushmsg::PushMessageInfo;
+use crate::pb::onlinepush::{GeneralGrayTipInfo, NotifyMsgBody, Sub8A, SubMsgType0x27MsgBody};
+use crate::servlet::olpush::dedup::is_duplicate_push;
+use crate::servlet::olpush::notice::{mark_group_msg_recalled, mark_msg_recalled, on_general_gray_tip, on_gray_tip_content, post_group_notice};
+
+pub(super) async fn on_push_message(bot: Arc<Bot>, info: PushMessageInfo) {
+    if is_duplicate_push((info.from_uin, info.msg_seq, info.msg_cookies.clone())) {
+        debug!("Duplicate push message, seq: {}, type: {}", info.msg_seq, info.msg_type);
+        return;
+    }
+    match info.msg_type {
+        528 => on_0x210(bot, info.v_msg, info.msg_time).await,
+        732 => on_0x2dc(bot, info.v_msg, info.msg_time).await,
+        _ => debug!("Unknown push message type: {}, v_msg: {}", info.msg_type, hex::encode(&info.v_msg))
+    }
+}
+
+async fn on_0x210(bot: Arc<Bot>, mut v_msg: Bytes, time: i64) {
+    let mut jr = Jce::new(&mut v_msg);
+    let sub_type = jr.get_by_tag::<i64>(0);
+    let content = jr.get_by_tag::<Bytes>(10);
+    let (Ok(sub_type), Ok(content)) = (sub_type, content) else {
+        warn!("Failed to decode 0x210 push: {}", hex::encode(&v_msg));
+        return;
+    };
+    match sub_type {
+        0x8a => on_friend_recall(bot, content).await,
+        0x27 => on_profile_change(bot, content, time),
+        0x122 => match GeneralGrayTipInfo::decode(content) {
+            Ok(tip) => on_general_gray_tip(&bot, 0, tip, time).await,
+            Err(e) => warn!("Failed to decode 0x210_0x122: {:?}", e)
+        },
+        _ => debug!("Unknown 0x210 sub_type: {:#x}, content: {}", sub_type, hex::encode(&content))
+    }
+}
+
+async fn on_friend_recall(bot: Arc<Bot>, content: Bytes) {
+    let Ok(body) = Sub8A::decode(content) else {
+        warn!("Failed to decode 0x210_0x8a");
+        return;
+    };
+    for info in body.msg_info {
+        let operator_uin = info.from_uin.unwrap_or_default();
+        let uin = if operator_uin == bot.unique_id {
+            info.to_uin.unwrap_or_default()
+        } else {
+            operator_uin
+        };
+        let msg_uid = info.msg_uid.unwrap_or_default();
+        mark_msg_recalled(&bot, msg_uid).await;
+        bot.post_event(BotEvent::Notice(NoticeEvent::FriendRecall {
+            uin,
+            uid: "".to_string(),
+            operator_uin,
+            msg_seq: info.msg_seq.unwrap_or_default() as i64,
+            msg_uid,
+            time: info.msg_time.unwrap_or_default(),
+        }));
+    }
+}
+
+fn on_profile_change(bot: Arc<Bot>, content: Bytes, time: i64) {
+    let Ok(body) = SubMsgType0x27MsgBody::decode(content) else {
+        warn!("Failed to decode 0x210_0x27");
+        return;
+    };
+    for info in body.mod_infos {
+        if let Some(profile) = info.mod_profile {
+            let uin = profile.uin.unwrap_or_default();
+            for item in profile.profile_infos {
+                // 20002: 昵称
+                if item.field == Some(20002) {
+                    bot.post_event(BotEvent::Notice(NoticeEvent::FriendNickChange {
+                        uin,
+                        nick: String::from_utf8_lossy(&item.value.unwrap_or_default()).into_owned(),
+                        time,
+                    }));
+                }
+            }
+        }
+        if let Some(del_friend) = info.del_friend {
+            for uin in del_friend.uins {
+                bot.post_event(BotEvent::Notice(NoticeEvent::FriendDelete { uin, time }));
+            }
+        }
+    }
+}
+
+async fn on_0x2dc(bot: Arc<Bot>, mut v_msg: Bytes, time: i64) {
+    if v_msg.remaining() < 6 {
+        warn!("Invalid 0x2dc push: {}", hex::encode(&v_msg));
+        return;
+    }
+    let group_id = v_msg.get_u32() as i64;
+    let sub_type = v_msg.get_u8();
+    v_msg.advance(1);
+    match sub_type {
+        0x0c if v_msg.remaining() >= 18 => {
+            let operator_uin = v_msg.get_u32() as i64;
+            v_msg.advance(6);
+            let target_uin = v_msg.get_u32() as i64;
+            let duration = v_msg.get_u32() as i64;
+            post_group_notice(&bot, NoticeEvent::GroupMute {
+                group_id,
+                operator_uin,
+                operator_uid: "".to_string(),
+                target_uin,
+                target_uid: "".to_string(),
+                duration,
+                time,
+            }).await;
+        }
+        0x10 | 0x11 | 0x14 | 0x15 if v_msg.has_remaining() => {
+            v_msg.advance(1);
+            match NotifyMsgBody::decode(v_msg) {
+                Ok(body) => on_group_notify(bot, group_id, body, time).await,
+                Err(e) => warn!("Failed to decode 0x2dc NotifyMsgBody: {:?}", e)
+            }
+        }
+        _ => debug!("Unknown 0x2dc sub_type: {:#x}, content: {}", sub_type, hex::encode(&v_msg))
+    }
+}
+
+async fn on_group_notify(bot: Arc<Bot>, group_id: i64, body: NotifyMsgBody, time: i64) {
+    if let Some(recall) = body.msg_recall {
+        let operator_uin = recall.uin.unwrap_or_default();
+        for msg in recall.recalled_msg_list {
+            let msg_seq = msg.seq.unwrap_or_default() as i64;
+            let msg_uid = mark_group_msg_recalled(&bot, group_id, msg_seq).await;
+            bot.post_event(BotEvent::Notice(NoticeEvent::GroupRecall {
+                group_id,
+                operator_uin,
+                operator_uid: "".to_string(),
+                sender_uin: msg.author_uin.unwrap_or_default(),
+                sender_uid: "".to_string(),
+                msg_seq,
+                msg_uid,
+                time: msg.time.unwrap_or_default() as i64,
+            }));
+        }
+    }
+    if let Some(tip) = body.general_gray_tip {
+        on_general_gray_tip(&bot, group_id, tip, time).await;
+    }
+    if let Some(tips) = body.msg_gray_tips {
+        let content = String::from_utf8_lossy(&tips.content.unwrap_or_default()).into_owned();
+        on_gray_tip_content(&bot, group_id, 0, content, time).await;
+    }
+}
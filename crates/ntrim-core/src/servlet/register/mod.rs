@@ -11,6 +11,8 @@ use crate::db;
 #[cfg(feature = "sql")]
 use crate::db::SimpleMessageRecord;
 use crate::pb::trpc::register::{ * };
+#[cfg(feature = "sql")]
+use crate::servlet::olpush::msg;
 
 pub struct RegisterProxyServlet(Arc<Bot>);
 
@@ -34,10 +36,22 @@ impl RegisterProxyServlet {
         #[cfg(feature = "sql")]
         if sync_push.push_flag == 5 && db::is_initialized() {
             let pool = db::PG_POOL.get().unwrap();
+            let mut catch_up = Vec::new();
             for node in sync_push.group_nodes {
+                let group_id = node.peer_id as i64;
+                // 与上次看到的seq比较，中间缺失的就是离线期间错过的消息
+                let mut seq = node.msg_seq as i64;
+                if let Ok(last) = SimpleMessageRecord::get_by_id(pool, group_id).await {
+                    if seq > last.seq {
+                        catch_up.push((group_id, last.seq + 1, seq));
+                        msg::mark_catching_up(&servlet.0, group_id);
+                        // 补齐成功之后才会更新到最新的seq
+                        seq = last.seq;
+                    }
+                }
                 let record = SimpleMessageRecord {
                     id: node.peer_id as i64,
-                    seq: node.msg_seq as i64,
+                    seq,
                     last_seq: node.longest_msg_seq.map_or(0, |v| v as i64),
                     name: node.peer_name.clone(),
                     latest_msg_time: NaiveDateTime::from_timestamp(node.latest_msg_time as i64, 0),
@@ -46,6 +60,14 @@ impl RegisterProxyServlet {
                     warn!("Failed to insert group_simple_record to pgsql: {:?}", e);
                 }).unwrap();
             }
+            if !catch_up.is_empty() {
+                let bot = Arc::clone(&servlet.0);
+                tokio::spawn(async move {
+                    for (group_id, from_seq, to_seq) in catch_up {
+                        msg::catch_up_group_msg(Arc::clone(&bot), group_id, from_seq, to_seq).await;
+                    }
+                });
+            }
         }
     }
 
@@ -0,0 +1,184 @@
+use std::io::Cursor;
+use anyhow::{anyhow, Error};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// QQ语音使用24k采样率的单声道silk
+pub const SILK_SAMPLE_RATE: u32 = 24000;
+/// silk每一帧20ms
+const SILK_FRAME_MS: u32 = 20;
+
+pub struct Silk {
+    pub data: Vec<u8>,
+    /// 时长，单位秒
+    pub duration: u32,
+}
+
+/// 是否为silk，腾讯的silk开头会多一个0x02
+pub fn is_silk(data: &[u8]) -> bool {
+    data.starts_with(b"#!SILK_V3") || data.starts_with(b"\x02#!SILK_V3")
+}
+
+/// 根据帧数计算silk的时长，单位秒
+pub fn silk_duration(data: &[u8]) -> u32 {
+    let header_len = if data.starts_with(b"\x02") { 10 } else { 9 };
+    let mut pos = header_len;
+    let mut frames = 0u32;
+    while pos + 2 <= data.len() {
+        let size = i16::from_le_bytes([data[pos], data[pos + 1]]);
+        if size <= 0 {
+            break;
+        }
+        pos += 2 + size as usize;
+        frames += 1;
+    }
+    (frames * SILK_FRAME_MS).div_ceil(1000).max(1)
+}
+
+/// 把wav/mp3/ogg等转换成silk，已经是silk的原样返回
+///
+/// symphonia解不了的格式（比如opus、aac）会交给`PATH`里面的ffmpeg解码
+pub fn encode_to_silk(data: &[u8]) -> Result<Silk, Error> {
+    if is_silk(data) {
+        return Ok(Silk {
+            data: data.to_vec(),
+            duration: silk_duration(data),
+        });
+    }
+    let (samples, sample_rate) = match decode_to_mono(data) {
+        Ok(decoded) => decoded,
+        Err(e) => decode_with_ffmpeg(data)
+            .map_err(|ffmpeg_err| anyhow!("Unsupported audio format: {} \
+                (only mp3/ogg vorbis/wav are supported without ffmpeg: {})", e, ffmpeg_err))?,
+    };
+    let samples = resample(&samples, sample_rate, SILK_SAMPLE_RATE);
+    if samples.is_empty() {
+        return Err(anyhow!("Empty audio"));
+    }
+    let duration = (samples.len() as u32).div_ceil(SILK_SAMPLE_RATE).max(1);
+    let pcm = samples.iter()
+        .flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+        .collect::<Vec<u8>>();
+    let data = silk_rs::encode_silk(pcm, SILK_SAMPLE_RATE as i32, SILK_SAMPLE_RATE as i32, true)
+        .map_err(|e| anyhow!("Failed to encode silk: {:?}", e))?;
+    Ok(Silk {
+        data,
+        duration,
+    })
+}
+
+/// 解码成单声道的pcm，返回(采样, 采样率)
+fn decode_to_mono(data: &[u8]) -> Result<(Vec<f32>, u32), Error> {
+    let source = MediaSourceStream::new(Box::new(Cursor::new(data.to_vec())), Default::default());
+    let probed = symphonia::default::get_probe()
+        .format(&Hint::new(), source, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| anyhow!("Unsupported audio format: {}", e))?;
+    let mut format = probed.format;
+    let track = format.default_track().ok_or(anyhow!("No audio track"))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(SILK_SAMPLE_RATE);
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // 损坏的帧直接跳过
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        let channels = spec.channels.count().max(1);
+        let mut buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buf.copy_interleaved_ref(decoded);
+        samples.extend(buf.samples().chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32));
+    }
+    Ok((samples, sample_rate))
+}
+
+/// 用ffmpeg解码成silk采样率的单声道pcm
+fn decode_with_ffmpeg(data: &[u8]) -> Result<(Vec<f32>, u32), Error> {
+    let input = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    std::fs::write(&input, data)?;
+    let output = std::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&input)
+        .args(["-f", "s16le", "-acodec", "pcm_s16le", "-ac", "1", "-ar"])
+        .arg(SILK_SAMPLE_RATE.to_string())
+        .arg("-")
+        .output();
+    let _ = std::fs::remove_file(&input);
+    match output {
+        Ok(output) if output.status.success() => {
+            let samples = output.stdout.chunks_exact(2)
+                .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / i16::MAX as f32)
+                .collect();
+            Ok((samples, SILK_SAMPLE_RATE))
+        }
+        Ok(output) => Err(anyhow!("ffmpeg exited with {}", output.status)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(anyhow!("ffmpeg is not found in PATH")),
+        Err(e) => Err(anyhow!("Failed to run ffmpeg: {}", e)),
+    }
+}
+
+/// 线性插值重采样，语音够用了
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let len = (samples.len() as u64 * to as u64 / from as u64) as usize;
+    let step = from as f64 / to as f64;
+    (0..len).map(|i| {
+        let pos = i as f64 * step;
+        let index = pos as usize;
+        let frac = (pos - index as f64) as f32;
+        let current = samples[index.min(samples.len() - 1)];
+        let next = samples[(index + 1).min(samples.len() - 1)];
+        current + (next - current) * frac
+    }).collect()
+}
+
+#[test]
+fn test_encode_wav_to_silk() {
+    // 1秒16k的440Hz正弦波
+    let rate = 16000u32;
+    let pcm = (0..rate).flat_map(|i| {
+        let v = (i as f32 * 440.0 * 2.0 * std::f32::consts::PI / rate as f32).sin();
+        ((v * 8000.0) as i16).to_le_bytes()
+    }).collect::<Vec<u8>>();
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + pcm.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&rate.to_le_bytes());
+    wav.extend_from_slice(&(rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(pcm.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&pcm);
+
+    let silk = encode_to_silk(&wav).unwrap();
+    assert!(is_silk(&silk.data));
+    assert_eq!(silk.duration, 1);
+    assert_eq!(silk_duration(&silk.data), 1);
+}
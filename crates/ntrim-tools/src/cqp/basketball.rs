@@ -1,6 +1,7 @@
 use std::collections::HashMap;
-use anyhow::{Error};
+use anyhow::Error;
 
+/// 投篮，`id`为结果(1-5)，发送时为0则随机
 #[derive(Debug, Default)]
 pub struct Basketball {
     pub id: i32,
@@ -13,9 +14,13 @@ impl std::fmt::Display for Basketball {
 }
 
 impl Basketball {
-    pub(crate) fn from(_params: &HashMap<String, String>) -> Result<Self, Error> {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        // 兼容使用result指定结果的实现
+        let id = params.get("id")
+            .or_else(|| params.get("result"))
+            .map_or(Ok(0), |s| s.parse::<i32>())?;
         Ok(Basketball {
-            ..Default::default()
+            id
         })
     }
 }
\ No newline at end of file
@@ -167,6 +167,12 @@ pub(crate) fn parse_special_cq(flag: &str, params: &HashMap<String, String>) ->
         "basketball" => Ok(CQCode::Basketball(Basketball::from(params)?)),
         "bubble_face" => Ok(CQCode::BubbleFace(BubbleFace::from(params)?)),
         "touch" => Ok(CQCode::Touch(Touch::from(params)?)),
+        "json" => Ok(CQCode::Json(Json::from(params)?)),
+        "xml" => Ok(CQCode::Xml(Xml::from(params)?)),
+        "forward" => Ok(CQCode::Forward(Forward::from(params)?)),
+        "file" => Ok(CQCode::File(File::from(params)?)),
+        "mface" => Ok(CQCode::MFace(MFace::from(params)?)),
+        "node" => Ok(CQCode::Node(Node::from(params)?)),
         &_ => {
             error!("Parse cqcode failed: unknown cq code, type: {}", flag);
             Err(anyhow!("Parse cqcode failed: unknown cq code, type: {}", flag))
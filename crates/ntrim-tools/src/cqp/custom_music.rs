@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
 
 #[derive(Debug, Default)]
 pub struct CustomMusic {
@@ -13,11 +14,16 @@ pub struct CustomMusic {
 
 impl std::fmt::Display for CustomMusic {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[CQ:music,type={},url={},audio={},title={},singer={}",
+               self.music_type,
+               encode_cq_code_param(&self.url),
+               encode_cq_code_param(&self.audio),
+               encode_cq_code_param(&self.title),
+               encode_cq_code_param(&self.singer))?;
         if let Some(image) = &self.image {
-            write!(f, "[CQ:music,type={},url={},audio={},title={},singer={},image={}]", self.music_type, self.url, self.audio, self.title, self.singer, image)
-        } else {
-            write!(f, "[CQ:music,type={},url={},audio={},title={},singer={}]", self.music_type, self.url, self.audio, self.title, self.singer)
+            write!(f, ",image={}", encode_cq_code_param(image))?;
         }
+        write!(f, "]")
     }
 }
 
@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
+
+/// 群文件，下载链接需要通过`id`和`busid`获取
+#[derive(Debug, Default)]
+pub struct File {
+    pub id: String,
+    pub name: String,
+    pub size: i64,
+    pub busid: u32,
+}
+
+impl Display for File {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[CQ:file,id={},name={},size={},busid={}]", encode_cq_code_param(&self.id), encode_cq_code_param(&self.name), self.size, self.busid)
+    }
+}
+
+impl File {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        let id = params.get("id").ok_or(anyhow!("File 缺少 'id' 参数"))?;
+        let name = params.get("name").map_or("".to_string(), |s| s.to_string());
+        let size = params.get("size").map_or(0, |s| s.parse::<i64>().unwrap_or(0));
+        let busid = params.get("busid").map_or(0, |s| s.parse::<u32>().unwrap_or(0));
+        Ok(File {
+            id: id.to_string(),
+            name,
+            size,
+            busid,
+        })
+    }
+}
@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
+
+/// 合并转发，`id`为resid
+#[derive(Debug, Default)]
+pub struct Forward {
+    pub id: String,
+}
+
+impl Display for Forward {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[CQ:forward,id={}]", encode_cq_code_param(&self.id))
+    }
+}
+
+impl Forward {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        let id = params.get("id").ok_or(anyhow!("Forward 缺少 'id' 参数"))?;
+        Ok(Forward {
+            id: id.to_string(),
+        })
+    }
+}
@@ -8,7 +8,8 @@ pub struct Image {
     pub file: String,
     pub url: String,
     pub r#type: String,
-    pub sub_type: u32
+    pub sub_type: u32,
+    pub summary: String,
 }
 
 impl Image {
@@ -21,7 +22,8 @@ impl Image {
             } else {
                 "show".to_string()
             },
-            sub_type: 0
+            sub_type: 0,
+            summary: "".to_string(),
         }
     }
 
@@ -30,14 +32,19 @@ impl Image {
             file,
             url,
             r#type: "show".to_string(),
-            sub_type
+            sub_type,
+            summary: "".to_string(),
         }
     }
 }
 
 impl Display for Image {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[CQ:image,file={},url={},type={},subType={}]", self.file, encode_cq_code_param(&self.url), self.r#type, self.sub_type)
+        write!(f, "[CQ:image,file={},url={},type={},subType={}", self.file, encode_cq_code_param(&self.url), self.r#type, self.sub_type)?;
+        if !self.summary.is_empty() {
+            write!(f, ",summary={}", encode_cq_code_param(&self.summary))?;
+        }
+        write!(f, "]")
     }
 }
 
@@ -48,6 +55,7 @@ impl Image {
         let url = params.get("url").unwrap_or(&binding);
         let r#type = params.get("type").unwrap_or(&binding);
         let sub_type = params.get("subType").map(|s| s.parse::<u32>().unwrap_or(0)).unwrap_or(0);
+        let summary = params.get("summary").unwrap_or(&binding);
         if file.is_empty() && url.is_empty() {
             return Err(anyhow!("file and url can't be empty"))
         }
@@ -55,7 +63,8 @@ impl Image {
             file: file.to_string(),
             url: url.to_string(),
             r#type: r#type.to_string(),
-            sub_type
+            sub_type,
+            summary: summary.to_string(),
         })
     }
 }
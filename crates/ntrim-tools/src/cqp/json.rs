@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
+
+/// 小程序卡片(LightApp)
+#[derive(Debug, Default)]
+pub struct Json {
+    pub data: String,
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[CQ:json,data={}]", encode_cq_code_param(&self.data))
+    }
+}
+
+impl Json {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        let data = params.get("data").ok_or(anyhow!("Json 缺少 'data' 参数"))?;
+        Ok(Json {
+            data: data.to_string(),
+        })
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
 
 #[derive(Debug, Default)]
 pub struct Location {
@@ -11,14 +12,14 @@ pub struct Location {
 
 impl std::fmt::Display for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[CQ:location,lat={},lon={}]", self.lat, self.lon)?;
+        write!(f, "[CQ:location,lat={},lon={}", self.lat, self.lon)?;
         if let Some(title) = &self.title {
-            write!(f, ",title={}", title)?;
+            write!(f, ",title={}", encode_cq_code_param(title))?;
         }
         if let Some(content) = &self.content {
-            write!(f, ",content={}", content)?;
+            write!(f, ",content={}", encode_cq_code_param(content))?;
         }
-        Ok(())
+        write!(f, "]")
     }
 }
 
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
+
+/// 商城表情
+#[derive(Debug, Default)]
+pub struct MFace {
+    pub emoji_id: String,
+    pub emoji_package_id: u32,
+    pub key: String,
+    pub summary: String,
+}
+
+impl Display for MFace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[CQ:mface,emoji_id={},emoji_package_id={},key={},summary={}]",
+               self.emoji_id, self.emoji_package_id, encode_cq_code_param(&self.key), encode_cq_code_param(&self.summary))
+    }
+}
+
+impl MFace {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        let emoji_id = params.get("emoji_id").ok_or(anyhow!("MFace 缺少 'emoji_id' 参数"))?;
+        let emoji_package_id = params.get("emoji_package_id").ok_or(anyhow!("MFace 缺少 'emoji_package_id' 参数"))?.parse::<u32>()?;
+        let key = params.get("key").map_or("".to_string(), |s| s.to_string());
+        let summary = params.get("summary").map_or("[动画表情]".to_string(), |s| s.to_string());
+        Ok(MFace {
+            emoji_id: emoji_id.to_string(),
+            emoji_package_id,
+            key,
+            summary,
+        })
+    }
+}
@@ -17,6 +17,12 @@ mod location;
 mod share;
 mod gift;
 mod custom_music;
+mod json;
+mod xml;
+mod forward;
+mod file;
+mod mface;
+mod node;
 mod segment_parser;
 
 pub use crate::cqp::at::At;
@@ -37,13 +43,21 @@ pub use crate::cqp::location::Location;
 pub use crate::cqp::share::Share;
 pub use crate::cqp::gift::Gift;
 pub use crate::cqp::custom_music::CustomMusic;
+pub use crate::cqp::json::Json;
+pub use crate::cqp::xml::Xml;
+pub use crate::cqp::forward::Forward;
+pub use crate::cqp::file::File;
+pub use crate::cqp::mface::MFace;
+pub use crate::cqp::node::Node;
 
 pub use cq_parser::parse_cq;
 pub use segment_parser::parse_segments;
 pub use segment_parser::parse_single_segment;
+pub use segment_parser::to_segments;
 use std::fmt::Display;
 
 
+#[derive(Debug)]
 pub enum CQCode {
     Text(String),
     At(At),
@@ -64,6 +78,12 @@ pub enum CQCode {
     Share(Share),
     Gift(Gift),
     CustomMusic(CustomMusic),
+    Json(Json),
+    Xml(Xml),
+    Forward(Forward),
+    File(File),
+    MFace(MFace),
+    Node(Node),
 }
 
 impl Display for CQCode {
@@ -88,6 +108,12 @@ impl Display for CQCode {
             CQCode::Share(share) => write!(f, "{}", share),
             CQCode::Gift(gift) => write!(f, "{}", gift),
             CQCode::CustomMusic(custom_music) => write!(f, "{}", custom_music),
+            CQCode::Json(json) => write!(f, "{}", json),
+            CQCode::Xml(xml) => write!(f, "{}", xml),
+            CQCode::Forward(forward) => write!(f, "{}", forward),
+            CQCode::File(file) => write!(f, "{}", file),
+            CQCode::MFace(mface) => write!(f, "{}", mface),
+            CQCode::Node(node) => write!(f, "{}", node),
         }
     }
 }
@@ -98,3 +124,10 @@ fn encode_cq_code_param(cq: &str) -> String {
         .replace("]", "&#93;")
         .replace(",", "&#44;")
 }
+
+fn decode_cq_code_param(cq: &str) -> String {
+    cq.replace("&#91;", "[")
+        .replace("&#93;", "]")
+        .replace("&#44;", ",")
+        .replace("&amp;", "&")
+}
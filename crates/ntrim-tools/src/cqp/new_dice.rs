@@ -1,6 +1,7 @@
 use std::collections::HashMap;
-use anyhow::{Error};
+use anyhow::Error;
 
+/// 骰子，`id`为点数(1-6)，发送时为0则随机
 #[derive(Debug, Default)]
 pub struct NewDice {
     pub id: i32,
@@ -8,14 +9,18 @@ pub struct NewDice {
 
 impl std::fmt::Display for NewDice {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[CQ:new_dice,id={}]", self.id)
+        write!(f, "[CQ:dice,id={}]", self.id)
     }
 }
 
 impl NewDice {
-    pub(crate) fn from(_params: &HashMap<String, String>) -> Result<Self, Error> {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        // 兼容使用result指定结果的实现
+        let id = params.get("id")
+            .or_else(|| params.get("result"))
+            .map_or(Ok(0), |s| s.parse::<i32>())?;
         Ok(NewDice {
-            ..Default::default()
+            id
         })
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use anyhow::Error;
 
+/// 猜拳，`id`为结果(1-3)，发送时为0则随机
 #[derive(Debug, Default)]
 pub struct NewRPS {
     pub id: i32,
@@ -8,14 +9,18 @@ pub struct NewRPS {
 
 impl std::fmt::Display for NewRPS {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[CQ:new_rps,id={}]", self.id)
+        write!(f, "[CQ:rps,id={}]", self.id)
     }
 }
 
 impl NewRPS {
-    pub(crate) fn from(_params: &HashMap<String, String>) -> Result<Self, Error> {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        // 兼容使用result指定结果的实现
+        let id = params.get("id")
+            .or_else(|| params.get("result"))
+            .map_or(Ok(0), |s| s.parse::<i32>())?;
         Ok(NewRPS {
-            ..Default::default()
+            id
         })
     }
 }
\ No newline at end of file
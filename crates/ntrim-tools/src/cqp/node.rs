@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use anyhow::{anyhow, Error};
+use crate::cqp::{encode_cq_code_param, parse_cq, parse_segments, CQCode};
+
+/// 合并转发的节点，`id`不为空时引用已有的消息，否则使用自定义的发送者和内容
+#[derive(Debug, Default)]
+pub struct Node {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub nickname: String,
+    pub content: Vec<CQCode>,
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(id) = self.id {
+            return write!(f, "[CQ:node,id={}]", id);
+        }
+        let content = self.content.iter().map(|x| x.to_string()).collect::<String>();
+        write!(f, "[CQ:node,user_id={},nickname={},content={}]", self.user_id, encode_cq_code_param(&self.nickname), encode_cq_code_param(&content))
+    }
+}
+
+impl Node {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        if let Some(id) = params.get("id") {
+            return Ok(Node {
+                id: Some(id.parse::<i64>()?),
+                ..Default::default()
+            });
+        }
+        // 兼容go-cqhttp的name/uin
+        let user_id = params.get("user_id").or(params.get("uin"))
+            .ok_or(anyhow!("Node 缺少 'user_id' 参数"))?
+            .parse::<i64>()?;
+        let nickname = params.get("nickname").or(params.get("name"))
+            .ok_or(anyhow!("Node 缺少 'nickname' 参数"))?;
+        let content = params.get("content").ok_or(anyhow!("Node 缺少 'content' 参数"))?;
+        // 消息段数组会被序列化成json字符串传进来
+        let content = match serde_json::from_str::<serde_json::Value>(content) {
+            Ok(value) if value.is_array() => parse_segments(value)?,
+            _ => parse_cq(content.as_bytes())?,
+        };
+        Ok(Node {
+            id: None,
+            user_id,
+            nickname: nickname.to_string(),
+            content,
+        })
+    }
+}
+
+#[test]
+fn test_parse_node() {
+    let params = HashMap::from([
+        ("name".to_string(), "伏秋洛".to_string()),
+        ("uin".to_string(), "10001".to_string()),
+        ("content".to_string(), r#"[{"type":"text","data":{"text":"hello"}},{"type":"face","data":{"id":"1"}}]"#.to_string()),
+    ]);
+    let node = Node::from(&params).unwrap();
+    assert_eq!(node.user_id, 10001);
+    assert_eq!(node.content.len(), 2);
+
+    let params = HashMap::from([("id".to_string(), "123".to_string())]);
+    assert_eq!(Node::from(&params).unwrap().id, Some(123));
+}
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 use anyhow::{anyhow, Error};
-use serde_json::Value;
+use serde_json::{json, Value};
 use crate::cqp::cq_parser::parse_special_cq;
-use crate::cqp::CQCode;
+use crate::cqp::{CQCode, decode_cq_code_param};
 
 fn json_to_hashmap(value: &Value) -> HashMap<String, String> {
     let mut hashmap = HashMap::new();
@@ -43,6 +43,27 @@ pub fn parse_segments(value: Value) -> Result<Vec<CQCode>, Error> {
     return Ok(result);
 }
 
+/// 将消息转换为OneBot消息段数组
+pub fn to_segments(elements: &[CQCode]) -> Value {
+    Value::Array(elements.iter().map(to_single_segment).collect())
+}
+
+fn to_single_segment(cq: &CQCode) -> Value {
+    if let CQCode::Text(text) = cq {
+        return json!({ "type": "text", "data": { "text": text } });
+    }
+    let code = cq.to_string();
+    let mut parts = code.trim_start_matches("[CQ:").trim_end_matches(']').split(',');
+    let flag = parts.next().unwrap_or_default().to_string();
+    let mut data = serde_json::Map::new();
+    for part in parts {
+        if let Some((key, val)) = part.split_once('=') {
+            data.insert(key.to_string(), Value::String(decode_cq_code_param(val)));
+        }
+    }
+    json!({ "type": flag, "data": data })
+}
+
 #[test]
 fn test_json_to_hashmap() {
     let json_value = serde_json::json!({
@@ -63,4 +84,24 @@ fn test_json_to_hashmap() {
     ].iter().cloned().collect();
 
     assert_eq!(result, expected);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_to_segments() {
+    let elements = crate::cqp::parse_cq("hello[CQ:face,id=14]".as_bytes()).unwrap();
+    let result = to_segments(&elements);
+    assert_eq!(result[0], json!({ "type": "text", "data": { "text": "hello" } }));
+    assert_eq!(result[1]["type"], "face");
+    assert_eq!(result[1]["data"]["id"], "14");
+}
+
+#[test]
+fn test_magic_face_segments() {
+    let elements = crate::cqp::parse_cq("[CQ:dice,id=3][CQ:rps,result=2][CQ:basketball]".as_bytes()).unwrap();
+    let result = to_segments(&elements);
+    assert_eq!(result[0], json!({ "type": "dice", "data": { "id": "3" } }));
+    assert_eq!(result[1], json!({ "type": "rps", "data": { "id": "2" } }));
+    assert_eq!(result[2], json!({ "type": "basketball", "data": { "id": "0" } }));
+    let dice = parse_single_segment(result[0].clone()).unwrap();
+    assert!(matches!(dice, CQCode::NewDice(crate::cqp::NewDice { id: 3 })));
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
 
 #[derive(Debug, Default)]
 pub struct Share {
@@ -12,20 +13,20 @@ pub struct Share {
 
 impl std::fmt::Display for Share {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[CQ:share,url={}]", self.url)?;
+        write!(f, "[CQ:share,url={}", encode_cq_code_param(&self.url))?;
         if let Some(title) = &self.title {
-            write!(f, ",title={}", title)?;
+            write!(f, ",title={}", encode_cq_code_param(title))?;
         }
         if let Some(content) = &self.content {
-            write!(f, ",content={}", content)?;
+            write!(f, ",content={}", encode_cq_code_param(content))?;
         }
         if let Some(image) = &self.image {
-            write!(f, ",image={}", image)?;
+            write!(f, ",image={}", encode_cq_code_param(image))?;
         }
         if let Some(file) = &self.file {
-            write!(f, ",file={}", file)?;
+            write!(f, ",file={}", encode_cq_code_param(file))?;
         }
-        Ok(())
+        write!(f, "]")
     }
 }
 
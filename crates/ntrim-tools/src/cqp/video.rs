@@ -7,6 +7,8 @@ use crate::cqp::encode_cq_code_param;
 pub struct Video {
     pub file: String,
     pub url: Option<String>,
+    /// 封面，发送的时候使用，没有的话自动生成
+    pub cover: Option<String>,
 }
 
 impl Display for Video {
@@ -23,9 +25,11 @@ impl Video {
     pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
         let file = params.get("file").ok_or(anyhow!("Video 缺少 'file' 参数"))?;
         let url = params.get("url").map(|s| s.to_string());
+        let cover = params.get("cover").map(|s| s.to_string());
         Ok(Video {
             file: file.to_string(),
             url,
+            cover,
         })
     }
 }
\ No newline at end of file
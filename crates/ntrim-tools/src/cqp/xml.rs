@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use anyhow::{anyhow, Error};
+use crate::cqp::encode_cq_code_param;
+
+/// 富文本卡片(RichMsg)
+#[derive(Debug, Default)]
+pub struct Xml {
+    pub data: String,
+    pub service_id: i32,
+}
+
+impl Display for Xml {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[CQ:xml,data={},service_id={}]", encode_cq_code_param(&self.data), self.service_id)
+    }
+}
+
+impl Xml {
+    pub(crate) fn from(params: &HashMap<String, String>) -> Result<Self, Error> {
+        let data = params.get("data").ok_or(anyhow!("Xml 缺少 'data' 参数"))?;
+        let service_id = params.get("service_id").map_or(1, |s| s.parse::<i32>().unwrap_or(1));
+        Ok(Xml {
+            data: data.to_string(),
+            service_id,
+        })
+    }
+}
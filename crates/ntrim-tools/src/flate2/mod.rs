@@ -1,17 +1,38 @@
 use std::io::{Read, Write};
 use flate2::Compression;
-use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
 
-pub fn decompress_deflate(encoded: &[u8]) -> Vec<u8> {
+/// 数据来自网络，解压失败时返回错误
+pub fn decompress_deflate(encoded: &[u8]) -> std::io::Result<Vec<u8>> {
     let mut decoder = ZlibDecoder::new(encoded);
     let mut decoded = Vec::new();
-    decoder.read_to_end(&mut decoded).unwrap();
-    decoded
+    decoder.read_to_end(&mut decoded)?;
+    Ok(decoded)
 }
 
 pub fn compress_deflate(decoded: &[u8]) -> Vec<u8> {
     let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
     encoder.write_all(decoded).unwrap();
     encoder.finish().unwrap()
-}
\ No newline at end of file
+}
+
+pub fn decompress_gzip(encoded: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoder = GzDecoder::new(encoded);
+    let mut decoded = Vec::new();
+    decoder.read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+pub fn compress_gzip(decoded: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(decoded).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn test_decompress_deflate() {
+    let data = "hello".repeat(10);
+    assert_eq!(decompress_deflate(&compress_deflate(data.as_bytes())).unwrap(), data.as_bytes());
+    assert!(decompress_deflate(b"not zlib data").is_err());
+}
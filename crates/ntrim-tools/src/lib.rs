@@ -1,6 +1,8 @@
 pub mod bytes;
 pub mod crypto;
 pub mod flate2;
+pub mod audio;
+pub mod video;
 pub mod sigint;
 pub mod tokiort;
 pub mod cqp;
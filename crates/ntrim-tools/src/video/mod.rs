@@ -0,0 +1,103 @@
+/// 从mp4容器里面读出来的视频信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    /// 时长，单位秒
+    pub duration: u32,
+}
+
+/// 解析mp4的moov，读取时长(mvhd)和分辨率(tkhd)
+pub fn parse_mp4_info(data: &[u8]) -> Option<VideoInfo> {
+    let moov = find_box(data, b"moov")?;
+    let mut info = VideoInfo::default();
+    if let Some(mvhd) = find_box(moov, b"mvhd") {
+        let version = *mvhd.first()?;
+        let (timescale, duration) = if version == 1 {
+            (read_u32(mvhd, 20)? as u64, read_u64(mvhd, 24)?)
+        } else {
+            (read_u32(mvhd, 12)? as u64, read_u32(mvhd, 16)? as u64)
+        };
+        if timescale > 0 {
+            info.duration = duration.div_ceil(timescale) as u32;
+        }
+    }
+    let mut pos = 0;
+    while let Some((name, body, next)) = next_box(moov, pos) {
+        pos = next;
+        if name != b"trak" {
+            continue;
+        }
+        let Some(tkhd) = find_box(body, b"tkhd") else { continue };
+        let version = *tkhd.first()?;
+        // 宽高在tkhd的最后8个字节，16.16定点数
+        let offset = if version == 1 { 88 } else { 76 };
+        let width = read_u32(tkhd, offset)? >> 16;
+        let height = read_u32(tkhd, offset + 4)? >> 16;
+        if width > 0 && height > 0 {
+            info.width = width;
+            info.height = height;
+            break;
+        }
+    }
+    Some(info)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|v| u32::from_be_bytes(v.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8).map(|v| u64::from_be_bytes(v.try_into().unwrap()))
+}
+
+/// 返回(类型, 内容, 下一个box的位置)
+fn next_box(data: &[u8], pos: usize) -> Option<(&[u8], &[u8], usize)> {
+    let size = read_u32(data, pos)? as u64;
+    let name = data.get(pos + 4..pos + 8)?;
+    let (header, size) = match size {
+        0 => (8, (data.len() - pos) as u64),
+        1 => (16, read_u64(data, pos + 8)?),
+        size => (8, size),
+    };
+    let end = pos.checked_add(size as usize)?;
+    if size < header || end > data.len() {
+        return None;
+    }
+    Some((name, &data[pos + header as usize..end], end))
+}
+
+fn find_box<'a>(data: &'a [u8], target: &[u8; 4]) -> Option<&'a [u8]> {
+    let mut pos = 0;
+    while let Some((name, body, next)) = next_box(data, pos) {
+        if name == target {
+            return Some(body);
+        }
+        pos = next;
+    }
+    None
+}
+
+#[test]
+fn test_parse_mp4_info() {
+    fn make_box(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut v = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        v.extend_from_slice(name);
+        v.extend_from_slice(body);
+        v
+    }
+    let mut mvhd = vec![0u8; 100];
+    mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+    mvhd[16..20].copy_from_slice(&12500u32.to_be_bytes());
+    let mut tkhd = vec![0u8; 84];
+    tkhd[76..80].copy_from_slice(&(1280u32 << 16).to_be_bytes());
+    tkhd[80..84].copy_from_slice(&(720u32 << 16).to_be_bytes());
+    let trak = make_box(b"trak", &make_box(b"tkhd", &tkhd));
+    let mut moov = make_box(b"mvhd", &mvhd);
+    moov.extend(trak);
+    let mut mp4 = make_box(b"ftyp", b"isom");
+    mp4.extend(make_box(b"moov", &moov));
+
+    assert_eq!(parse_mp4_info(&mp4), Some(VideoInfo { width: 1280, height: 720, duration: 13 }));
+    assert_eq!(parse_mp4_info(b"not a video"), None);
+}
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct GetFriendSystemMsgParams {
+}
+
+async fn handle_get_friend_system_msg(bot: &Arc<Bot>, _params: GetFriendSystemMsgParams) -> actix_web::Result<impl serde::Serialize> {
+    let msgs = Bot::get_friend_system_msg(bot).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to get_friend_system_msg: {}", e)))?;
+    Ok(msgs.into_iter().map(|msg| json!({
+        "request_id": msg.msg_seq,
+        "requester_uin": msg.req_uin,
+        "requester_nick": msg.req_nick,
+        "message": msg.comment,
+        "source": msg.source,
+        "flag": msg.flag(),
+        "checked": msg.decided,
+        "time": msg.msg_time,
+    })).collect::<Vec<_>>())
+}
+
+init_route!("/get_friend_system_msg", GetFriendSystemMsgParams, handle_get_friend_system_msg);
@@ -8,3 +8,5 @@ pub(crate) mod get_group_list;
 pub(crate) mod get_group_member_list;
 pub(crate) mod get_group_member_info;
 pub(crate) mod send_like;
+pub(crate) mod set_friend_add_request;
+pub(crate) mod get_friend_system_msg;
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct SetFriendAddRequestParams {
+    flag: String,
+    approve: Option<bool>,
+    remark: Option<String>
+}
+
+async fn handle_set_friend_add_request(bot: &Arc<Bot>, params: SetFriendAddRequestParams) -> actix_web::Result<impl serde::Serialize> {
+    Bot::set_friend_add_request(bot, &params.flag, params.approve.unwrap_or(true), params.remark).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to set_friend_add_request: {}", e)))?;
+    Ok(json!({}))
+}
+
+init_route!("/set_friend_add_request", SetFriendAddRequestParams, handle_set_friend_add_request);
@@ -0,0 +1,22 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct GetGroupFileUrlParams {
+    group_id: i64,
+    file_id: String,
+    busid: u32,
+}
+
+async fn handle_get_group_file_url(bot: &Arc<Bot>, params: GetGroupFileUrlParams) -> actix_web::Result<impl serde::Serialize> {
+    let url = Bot::get_group_file_url(bot, params.group_id, params.file_id, params.busid).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to get group file url: {}", e)))?;
+    Ok(json!({
+        "url": url
+    }))
+}
+
+init_route!("/get_group_file_url", GetGroupFileUrlParams, handle_get_group_file_url);
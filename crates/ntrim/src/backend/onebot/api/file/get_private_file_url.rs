@@ -0,0 +1,21 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct GetPrivateFileUrlParams {
+    file_id: String,
+    file_hash: String,
+}
+
+async fn handle_get_private_file_url(bot: &Arc<Bot>, params: GetPrivateFileUrlParams) -> actix_web::Result<impl serde::Serialize> {
+    let url = Bot::get_offline_file_url(bot, params.file_id, params.file_hash).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to get private file url: {}", e)))?;
+    Ok(json!({
+        "url": url
+    }))
+}
+
+init_route!("/get_private_file_url", GetPrivateFileUrlParams, handle_get_private_file_url);
@@ -0,0 +1,2 @@
+pub mod get_group_file_url;
+pub mod get_private_file_url;
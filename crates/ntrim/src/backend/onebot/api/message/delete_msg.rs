@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use log::warn;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use ntrim_core::Contact;
+use ntrim_core::service::msg::SendMsgResult;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct DeleteMsgParams {
+    message_id: i64,
+}
+
+async fn handle_delete_msg(bot: &Arc<Bot>, params: DeleteMsgParams) -> actix_web::Result<impl serde::Serialize> {
+    let message_id = match i32::try_from(params.message_id) {
+        Ok(id) => Bot::get_message_id(bot, id).await,
+        Err(_) => None
+    };
+    let message_id = message_id
+        .ok_or(OnebotError::IllegalInputError(format!("Unknown message_id: {}", params.message_id)))?;
+    Bot::recall_msg(bot, &message_id.contact, message_id.msg_seq, message_id.client_seq, message_id.msg_uid, message_id.msg_time).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to delete msg: {}", e)))?;
+    Ok(json!({}))
+}
+
+/// `recall_duration`毫秒后自动撤回刚发出去的消息
+pub(super) fn schedule_recall(bot: &Arc<Bot>, contact: Contact, result: &SendMsgResult, duration: i64) {
+    let bot = Arc::clone(bot);
+    let result = result.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(tokio::time::Duration::from_millis(duration as u64)).await;
+        if let Err(e) = Bot::recall_msg(&bot, &contact, result.msg_seq, result.client_seq, result.msg_uid, result.msg_time).await {
+            warn!("Failed to recall msg {} automatically: {}", result.msg_seq, e);
+        }
+    });
+}
+
+init_route!("/delete_msg", DeleteMsgParams, handle_delete_msg);
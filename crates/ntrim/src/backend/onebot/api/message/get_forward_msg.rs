@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use ntrim_core::bot::Bot;
+use ntrim_tools::cqp::to_segments;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct GetForwardMsgParams {
+    /// 兼容go-cqhttp的message_id
+    #[serde(alias = "message_id")]
+    id: String,
+}
+
+async fn handle_get_forward_msg(bot: &Arc<Bot>, params: GetForwardMsgParams) -> actix_web::Result<impl serde::Serialize> {
+    let records = Bot::get_forward_msg(bot, params.id).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to get forward msg: {}", e)))?;
+    let messages: Vec<_> = records.iter().map(|record| {
+        let content = match std::env::var("MESSAGE_POST_FORMAT").as_deref() {
+            Ok("array") => to_segments(&record.elements),
+            _ => Value::String(record.to_raw_msg()),
+        };
+        json!({
+            "time": record.msg_time,
+            "sender": {
+                "user_id": record.sender_id,
+                "nickname": record.sender_nick,
+            },
+            "content": content,
+        })
+    }).collect();
+    Ok(json!({
+        "messages": messages
+    }))
+}
+
+init_route!("/get_forward_msg", GetForwardMsgParams, handle_get_forward_msg);
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use crate::backend::onebot::event::encode_message;
+use crate::backend::onebot::api::message::get_group_msg_history::history_range;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct GetFriendMsgHistoryParams {
+    user_id: i64,
+    message_seq: i64,
+    count: Option<i64>,
+}
+
+async fn handle_get_friend_msg_history(bot: &Arc<Bot>, params: GetFriendMsgHistoryParams) -> actix_web::Result<impl serde::Serialize> {
+    let (start_seq, count) = history_range(params.message_seq, params.count)?;
+    let records = Bot::get_friend_msg_history(bot, params.user_id, start_seq, count).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to get friend msg history: {}", e)))?;
+    let mut messages = Vec::with_capacity(records.len());
+    for record in records.iter() {
+        messages.push(encode_message(bot, record).await);
+    }
+    Ok(json!({
+        "messages": messages
+    }))
+}
+
+init_route!("/get_friend_msg_history", GetFriendMsgHistoryParams, handle_get_friend_msg_history);
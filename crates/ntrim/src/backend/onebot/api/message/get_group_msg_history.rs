@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use crate::backend::onebot::event::encode_message;
+use crate::init_route;
+
+/// 单次最多拉取的消息数量
+const MAX_HISTORY_COUNT: i64 = 100;
+
+#[derive(Deserialize, Debug)]
+struct GetGroupMsgHistoryParams {
+    group_id: i64,
+    /// 为空时从最新的消息开始
+    message_seq: Option<i64>,
+    count: Option<i64>,
+}
+
+async fn handle_get_group_msg_history(bot: &Arc<Bot>, params: GetGroupMsgHistoryParams) -> actix_web::Result<impl serde::Serialize> {
+    let message_seq = match params.message_seq {
+        Some(seq) => seq,
+        None => latest_group_seq(params.group_id).await
+            .ok_or(OnebotError::IllegalInputError("message_seq is required".to_string()))?,
+    };
+    let (start_seq, count) = history_range(message_seq, params.count)?;
+    let records = Bot::get_group_msg_history(bot, params.group_id, start_seq, count).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to get group msg history: {}", e)))?;
+    let mut messages = Vec::with_capacity(records.len());
+    for record in records.iter() {
+        messages.push(encode_message(bot, record).await);
+    }
+    Ok(json!({
+        "messages": messages
+    }))
+}
+
+/// 计算拉取的起始seq和数量，起始seq最小为1，数量最多为`MAX_HISTORY_COUNT`
+pub(super) fn history_range(message_seq: i64, count: Option<i64>) -> Result<(i64, i64), OnebotError> {
+    let count = count.unwrap_or(20);
+    if count <= 0 {
+        return Err(OnebotError::IllegalInputError(format!("Invalid count: {}", count)));
+    }
+    if message_seq <= 0 {
+        return Err(OnebotError::IllegalInputError(format!("Invalid message_seq: {}", message_seq)));
+    }
+    let start_seq = (message_seq - count.min(MAX_HISTORY_COUNT) + 1).max(1);
+    Ok((start_seq, message_seq - start_seq + 1))
+}
+
+async fn latest_group_seq(group_id: i64) -> Option<i64> {
+    #[cfg(feature = "sql")]
+    if ntrim_core::db::is_initialized() {
+        let pool = ntrim_core::db::PG_POOL.get().unwrap();
+        return ntrim_core::db::SimpleMessageRecord::get_by_id(pool, group_id).await
+            .ok()
+            .map(|record| record.seq);
+    }
+    None
+}
+
+init_route!("/get_group_msg_history", GetGroupMsgHistoryParams, handle_get_group_msg_history);
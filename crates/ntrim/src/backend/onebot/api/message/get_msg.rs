@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use ntrim_core::bot::Bot;
+use ntrim_core::Contact;
+use crate::backend::onebot::event::encode_message;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct GetMsgParams {
+    message_id: i32,
+}
+
+async fn handle_get_msg(bot: &Arc<Bot>, params: GetMsgParams) -> actix_web::Result<impl serde::Serialize> {
+    let message_id = Bot::get_message_id(bot, params.message_id).await
+        .ok_or(OnebotError::IllegalInputError(format!("Unknown message_id: {}", params.message_id)))?;
+    let records = match &message_id.contact {
+        Contact::Group(_, group_id) => Bot::get_group_msg_history(bot, *group_id, message_id.msg_seq, 1).await,
+        Contact::Friend(_, uin, _) | Contact::Stranger(_, uin, _) => Bot::get_friend_msg_history(bot, *uin, message_id.msg_seq, 1).await,
+    }.map_err(|e| OnebotError::InternalError(format!("Failed to get msg: {}", e)))?;
+    let record = records.iter()
+        .find(|record| record.msg_seq == message_id.msg_seq)
+        .ok_or(OnebotError::InternalError(format!("Message not found: {}", params.message_id)))?;
+    let mut message = encode_message(bot, record).await;
+    message["real_id"] = serde_json::json!(record.msg_seq);
+    Ok(message)
+}
+
+init_route!("/get_msg", GetMsgParams, handle_get_msg);
@@ -1,2 +1,11 @@
 pub mod send_private_msg;
 pub mod send_group_msg;
+pub mod send_poke;
+pub mod get_group_msg_history;
+pub mod get_friend_msg_history;
+pub mod send_group_forward_msg;
+pub mod send_private_forward_msg;
+pub mod get_forward_msg;
+pub mod delete_msg;
+pub mod get_msg;
+pub mod set_msg_emoji_like;
\ No newline at end of file
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use ntrim_core::bot::Bot;
+use ntrim_core::Contact;
+use ntrim_tools::cqp::{parse_segments, CQCode, Node};
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct SendGroupForwardMsgParams {
+    group_id: i64,
+    messages: Value,
+}
+
+async fn handle_send_group_forward_msg(bot: &Arc<Bot>, params: SendGroupForwardMsgParams) -> actix_web::Result<impl serde::Serialize> {
+    let nodes = parse_nodes(params.messages)?;
+    let (res_id, result) = Bot::send_forward_msg(bot, Contact::Group("".to_string(), params.group_id), nodes).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to send forward message: {}", e)))?;
+    Ok(json!({
+        "message_id": result.message_id,
+        "forward_id": res_id
+    }))
+}
+
+/// 只保留node消息段
+pub(super) fn parse_nodes(messages: Value) -> Result<Vec<Node>, OnebotError> {
+    if !messages.is_array() {
+        return Err(OnebotError::IllegalInputError("messages must be an array of node".to_string()));
+    }
+    let nodes: Vec<Node> = parse_segments(messages)
+        .map_err(|e| OnebotError::IllegalInputError(format!("Failed to parse messages: {}", e)))?
+        .into_iter()
+        .filter_map(|cq| match cq {
+            CQCode::Node(node) => Some(node),
+            _ => None
+        })
+        .collect();
+    if nodes.is_empty() {
+        return Err(OnebotError::IllegalInputError("messages must contain at least one node".to_string()));
+    }
+    Ok(nodes)
+}
+
+init_route!("/send_group_forward_msg", SendGroupForwardMsgParams, handle_send_group_forward_msg);
@@ -6,6 +6,7 @@ use ntrim_core::Contact;
 use ntrim_tools::cqp::{CQCode, parse_cq, parse_single_segment};
 use ntrim_tools::cqp::parse_segments;
 use crate::backend::UID_UIN_MAP;
+use crate::backend::onebot::api::message::delete_msg::schedule_recall;
 use crate::init_route;
 
 #[derive(Deserialize, Debug)]
@@ -35,10 +36,14 @@ async fn handle_send_group_msg(bot: &Arc<Bot>, params: SendGroupMessageParams) -
         Value::Array(..) => parse_segments(params.message),
         Value::Object(..) => parse_single_segment(params.message).map(|scq| vec![scq])
     }.map_err(|e| OnebotError::InternalError(format!("Failed to parse message: {}", e)))?;
-    let result = Bot::send_msg(bot, Contact::Group("".to_string(), params.group_id), msg).await
+    let contact = Contact::Group("".to_string(), params.group_id);
+    let result = Bot::send_msg(bot, contact.clone(), msg).await
         .map_err(|e| OnebotError::InternalError(format!("Failed to send message: {}", e)))?;
+    if let Some(duration) = params.recall_duration.filter(|duration| *duration > 0) {
+        schedule_recall(bot, contact, &result, duration);
+    }
     Ok(json!({
-        "message_id": result
+        "message_id": result.message_id
     }))
 }
 
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use ntrim_core::Contact;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct SendPokeParams {
+    user_id: i64,
+    group_id: Option<i64>,
+}
+
+async fn handle_send_poke(bot: &Arc<Bot>, params: SendPokeParams) -> actix_web::Result<impl serde::Serialize> {
+    let contact = match params.group_id {
+        Some(group_id) => Contact::Group("".to_string(), group_id),
+        None => Contact::Friend("".to_string(), params.user_id, "".to_string()),
+    };
+    Bot::send_poke(bot, contact, params.user_id).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to send poke: {}", e)))?;
+    Ok(json!({}))
+}
+
+init_route!("/send_poke", SendPokeParams, handle_send_poke);
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use ntrim_core::bot::Bot;
+use ntrim_core::Contact;
+use crate::backend::onebot::api::message::send_group_forward_msg::parse_nodes;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct SendPrivateForwardMsgParams {
+    user_id: i64,
+    messages: Value,
+}
+
+async fn handle_send_private_forward_msg(bot: &Arc<Bot>, params: SendPrivateForwardMsgParams) -> actix_web::Result<impl serde::Serialize> {
+    let nodes = parse_nodes(params.messages)?;
+    let uid = Bot::get_friend_uid(bot, params.user_id).await
+        .ok_or(OnebotError::IllegalInputError(format!("Unable to find uid of friend {}", params.user_id)))?;
+    let (res_id, result) = Bot::send_forward_msg(bot, Contact::Friend("".to_string(), params.user_id, uid), nodes).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to send forward message: {}", e)))?;
+    Ok(json!({
+        "message_id": result.message_id,
+        "forward_id": res_id
+    }))
+}
+
+init_route!("/send_private_forward_msg", SendPrivateForwardMsgParams, handle_send_private_forward_msg);
@@ -6,6 +6,7 @@ use ntrim_core::Contact;
 use ntrim_tools::cqp::{CQCode, parse_cq, parse_single_segment};
 use ntrim_tools::cqp::parse_segments;
 use crate::backend::UID_UIN_MAP;
+use crate::backend::onebot::api::message::delete_msg::schedule_recall;
 use crate::init_route;
 
 #[derive(Deserialize, Debug)]
@@ -53,10 +54,14 @@ async fn handle_send_private_msg(bot: &Arc<Bot>, params: SendPrivateMessageParam
     } else {
         UID_UIN_MAP.get(&params.user_id).unwrap().clone()
     };
-    let result = Bot::send_msg(bot, Contact::Friend("".to_string(), 0, uid), msg).await
+    let contact = Contact::Friend("".to_string(), params.user_id, uid);
+    let result = Bot::send_msg(bot, contact.clone(), msg).await
         .map_err(|e| OnebotError::InternalError(format!("Failed to send message: {}", e)))?;
+    if let Some(duration) = params.recall_duration.filter(|duration| *duration > 0) {
+        schedule_recall(bot, contact, &result, duration);
+    }
     Ok(json!({
-        "message_id": result
+        "message_id": result.message_id
     }))
 }
 
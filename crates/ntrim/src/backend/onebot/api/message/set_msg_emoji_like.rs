@@ -0,0 +1,34 @@
+use std::sync::Arc;
+use serde_derive::Deserialize;
+use serde_json::json;
+use ntrim_core::bot::Bot;
+use ntrim_core::Contact;
+use crate::init_route;
+
+#[derive(Deserialize, Debug)]
+struct SetMsgEmojiLikeParams {
+    message_id: i64,
+    emoji_id: serde_json::Value,
+    /// 为false时取消回应
+    set: Option<bool>,
+}
+
+async fn handle_set_msg_emoji_like(bot: &Arc<Bot>, params: SetMsgEmojiLikeParams) -> actix_web::Result<impl serde::Serialize> {
+    let emoji_id = match params.emoji_id {
+        serde_json::Value::String(emoji_id) => emoji_id,
+        serde_json::Value::Number(emoji_id) => emoji_id.to_string(),
+        _ => return Err(OnebotError::IllegalInputError("Invalid emoji_id".to_string()).into())
+    };
+    let message_id = match i32::try_from(params.message_id) {
+        Ok(id) => Bot::get_message_id(bot, id).await,
+        Err(_) => None
+    }.ok_or(OnebotError::IllegalInputError(format!("Message {} not found", params.message_id)))?;
+    let Contact::Group(_, group_id) = message_id.contact else {
+        return Err(OnebotError::IllegalInputError("Emoji like is only supported for group messages".to_string()).into());
+    };
+    Bot::set_msg_emoji_like(bot, group_id, message_id.msg_seq, emoji_id, params.set.unwrap_or(true)).await
+        .map_err(|e| OnebotError::InternalError(format!("Failed to set msg emoji like: {}", e)))?;
+    Ok(json!({}))
+}
+
+init_route!("/set_msg_emoji_like", SetMsgEmojiLikeParams, handle_set_msg_emoji_like);
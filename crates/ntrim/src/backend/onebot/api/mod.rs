@@ -1,5 +1,6 @@
 pub(crate) mod account;
 pub(crate) mod message;
+pub(crate) mod file;
 
 use actix_web::http::StatusCode;
 use serde_derive::{Deserialize, Serialize};
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+use serde_json::{json, Value};
+use ntrim_core::bot::Bot;
+use ntrim_core::events::{BotEvent, GroupHonor, NoticeEvent, RequestEvent};
+use ntrim_core::{Contact, MessageRecord};
+use ntrim_core::service::msg::MessageId;
+use ntrim_tools::cqp::to_segments;
+
+/// 将事件转换为OneBot上报格式，OneBot没有对应事件时返回None
+pub(super) async fn encode_event(bot: &Arc<Bot>, event: &BotEvent) -> Option<Value> {
+    let bot_id = bot.unique_id;
+    match event {
+        BotEvent::Message(record) => Some(encode_message(bot, record).await),
+        BotEvent::Notice(notice) => encode_notice(bot, notice).await,
+        BotEvent::Request(request) => Some(encode_request(bot_id, request)),
+    }
+}
+
+/// 生命周期元事件，连接建立时推送
+pub(super) fn lifecycle_connect(bot_id: i64) -> Value {
+    json!({
+        "time": chrono::Local::now().timestamp(),
+        "self_id": bot_id,
+        "post_type": "meta_event",
+        "meta_event_type": "lifecycle",
+        "sub_type": "connect",
+    })
+}
+
+/// 消息id以记录过的为准，历史消息之类没有记录过的会分配一个
+pub(super) async fn encode_message(bot: &Arc<Bot>, record: &MessageRecord) -> Value {
+    let bot_id = bot.unique_id;
+    let message_id = bot.save_message_id(MessageId::from_record(bot_id, record)).await.id;
+    let raw_message = record.to_raw_msg();
+    let message = match std::env::var("MESSAGE_POST_FORMAT").as_deref() {
+        Ok("array") => to_segments(&record.elements),
+        _ => Value::String(raw_message.clone()),
+    };
+    let mut event = json!({
+        "time": record.msg_time,
+        "self_id": bot_id,
+        // 自己在其它设备上发送的消息
+        "post_type": if record.is_self { "message_sent" } else { "message" },
+        "message_id": message_id,
+        "user_id": record.sender_id,
+        "message": message,
+        "raw_message": raw_message,
+        "font": 0,
+    });
+    match &record.contact {
+        Contact::Group(_, group_id) => {
+            event["message_type"] = json!("group");
+            event["sub_type"] = json!("normal");
+            event["group_id"] = json!(group_id);
+            event["anonymous"] = Value::Null;
+            event["sender"] = json!({
+                "user_id": record.sender_id,
+                "nickname": record.sender_nick,
+                "card": record.sender_nick,
+                "title": record.sender_unique_title,
+            });
+        }
+        Contact::Friend(_, uin, _) | Contact::Stranger(_, uin, _) => {
+            event["message_type"] = json!("private");
+            event["target_id"] = json!(uin);
+            event["sub_type"] = json!(if let Contact::Friend(..) = record.contact { "friend" } else { "other" });
+            event["sender"] = json!({
+                "user_id": record.sender_id,
+                "nickname": record.sender_nick,
+            });
+        }
+    }
+    event
+}
+
+/// 撤回、表情回应通知只有会话和msg_seq，通过它们找到记录过的消息id，没有记录过的消息会分配一个
+async fn notice_message_id(bot: &Arc<Bot>, contact: Contact, msg_seq: i64, msg_uid: i64) -> i32 {
+    bot.save_message_id(MessageId::new(bot.unique_id, contact, msg_seq, msg_uid, 0)).await.id
+}
+
+async fn encode_notice(bot: &Arc<Bot>, notice: &NoticeEvent) -> Option<Value> {
+    let bot_id = bot.unique_id;
+    let (time, mut event) = match notice {
+        NoticeEvent::FriendAdd { uin, time, .. } => (time, json!({
+            "notice_type": "friend_add",
+            "user_id": uin,
+        })),
+        NoticeEvent::GroupRecall { group_id, operator_uin, sender_uin, msg_seq, msg_uid, time, .. } => (time, json!({
+            "notice_type": "group_recall",
+            "group_id": group_id,
+            "user_id": sender_uin,
+            "operator_id": operator_uin,
+            "message_id": notice_message_id(bot, Contact::Group("".to_string(), *group_id), *msg_seq, *msg_uid).await,
+        })),
+        NoticeEvent::FriendRecall { uin, uid, operator_uin, msg_seq, msg_uid, time } => (time, json!({
+            "notice_type": "friend_recall",
+            "user_id": uin,
+            "operator_id": operator_uin,
+            "message_id": notice_message_id(bot, Contact::Friend("".to_string(), *uin, uid.clone()), *msg_seq, *msg_uid).await,
+        })),
+        NoticeEvent::GroupReaction { group_id, operator_uin, msg_seq, emoji_id, count, is_add, time, .. } => (time, json!({
+            "notice_type": "group_msg_emoji_like",
+            "group_id": group_id,
+            "user_id": operator_uin,
+            "message_id": notice_message_id(bot, Contact::Group("".to_string(), *group_id), *msg_seq, 0).await,
+            "likes": [{
+                "emoji_id": emoji_id,
+                "count": count,
+            }],
+            "is_add": is_add,
+        })),
+        NoticeEvent::GroupMute { group_id, operator_uin, target_uin, duration, time, .. } => (time, json!({
+            "notice_type": "group_ban",
+            "sub_type": if *duration == 0 { "lift_ban" } else { "ban" },
+            "group_id": group_id,
+            "operator_id": operator_uin,
+            "user_id": target_uin,
+            "duration": duration,
+        })),
+        NoticeEvent::GroupAdminChange { group_id, uin, is_admin, time, .. } => (time, json!({
+            "notice_type": "group_admin",
+            "sub_type": if *is_admin { "set" } else { "unset" },
+            "group_id": group_id,
+            "user_id": uin,
+        })),
+        NoticeEvent::GroupSpecialTitleChange { group_id, uin, special_title, time } => (time, json!({
+            "notice_type": "notify",
+            "sub_type": "title",
+            "group_id": group_id,
+            "user_id": uin,
+            "title": special_title,
+        })),
+        NoticeEvent::Poke { group_id, sender_uin, target_uin, action, suffix, time } => {
+            let mut event = json!({
+                "notice_type": "notify",
+                "sub_type": "poke",
+                "user_id": sender_uin,
+                "sender_id": sender_uin,
+                "target_id": target_uin,
+                "action": action,
+                "suffix": suffix,
+            });
+            if *group_id != 0 {
+                event["group_id"] = json!(group_id);
+            }
+            (time, event)
+        }
+        NoticeEvent::GroupHonorChange { group_id, uin, honor, time } => (time, json!({
+            "notice_type": "notify",
+            "sub_type": "honor",
+            "group_id": group_id,
+            "user_id": uin,
+            "honor_type": match honor {
+                GroupHonor::Talkative => "talkative",
+                GroupHonor::Performer => "performer",
+                GroupHonor::Emotion => "emotion",
+            },
+        })),
+        NoticeEvent::GroupFileUpload { group_id, uin, file_id, name, size, busid, time, .. } => (time, json!({
+            "notice_type": "group_upload",
+            "group_id": group_id,
+            "user_id": uin,
+            "file": {
+                "id": file_id,
+                "name": name,
+                "size": size,
+                "busid": busid,
+            },
+        })),
+        NoticeEvent::OfflineFile { uin, file_id, file_hash, name, size, time, .. } => (time, json!({
+            "notice_type": "offline_file",
+            "user_id": uin,
+            "file": {
+                "id": file_id,
+                "hash": file_hash,
+                "name": name,
+                "size": size,
+            },
+        })),
+        NoticeEvent::FriendNickChange { .. } |
+        NoticeEvent::FriendDelete { .. } |
+        NoticeEvent::GroupGrayTip { .. } => return None,
+    };
+    event["time"] = json!(time);
+    event["self_id"] = json!(bot_id);
+    event["post_type"] = json!("notice");
+    Some(event)
+}
+
+fn encode_request(bot_id: i64, request: &RequestEvent) -> Value {
+    match request {
+        RequestEvent::FriendRequest { uin, comment, flag, time, .. } => json!({
+            "time": time,
+            "self_id": bot_id,
+            "post_type": "request",
+            "request_type": "friend",
+            "user_id": uin,
+            "comment": comment,
+            "flag": flag,
+        }),
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::Error;
 use ntrim_core::bot::Bot;
 use crate::backend::onebot::api::account::{ * };
 use crate::backend::onebot::api::message::{ * };
+use crate::backend::onebot::api::file::{ * };
 
 pub(super) async fn start(bot: Arc<Bot>, host: String, port: u16) -> Result<(), Error> {
     HttpServer::new(move || {
@@ -19,8 +20,21 @@ pub(super) async fn start(bot: Arc<Bot>, host: String, port: u16) -> Result<(),
             .configure(get_group_member_info::register)
             .configure(set_qq_profile::register)
             .configure(send_like::register)
+            .configure(set_friend_add_request::register)
+            .configure(get_friend_system_msg::register)
             .configure(send_private_msg::register)
             .configure(send_group_msg::register)
+            .configure(send_poke::register)
+            .configure(get_group_msg_history::register)
+            .configure(get_friend_msg_history::register)
+            .configure(send_group_forward_msg::register)
+            .configure(send_private_forward_msg::register)
+            .configure(get_forward_msg::register)
+            .configure(delete_msg::register)
+            .configure(get_msg::register)
+            .configure(set_msg_emoji_like::register)
+            .configure(get_group_file_url::register)
+            .configure(get_private_file_url::register)
     })
         .bind((host, port))?
         .run()
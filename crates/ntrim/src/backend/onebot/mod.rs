@@ -1,5 +1,7 @@
 mod http;
+mod ws;
 mod api;
+mod event;
 
 use std::sync::Arc;
 use ntrim_core::bot::Bot;
@@ -7,8 +9,12 @@ use crate::config::OneBot;
 
 
 pub async fn launch(bot: Arc<Bot>, onebot: OneBot) {
+    if onebot.ws.enable {
+        let server = ws::start(bot.clone(), onebot.ws.host, onebot.ws.port).unwrap();
+        tokio::spawn(server);
+    }
     if onebot.http.enable {
         http::start(bot.clone(), onebot.http.host, onebot.http.port)
             .await.unwrap();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer, web};
+use actix_web::dev::Server;
+use actix_ws::Message;
+use anyhow::Error;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::StreamExt;
+use ntrim_core::bot::Bot;
+use crate::backend::onebot::event::{encode_event, lifecycle_connect};
+
+/// 主动WS，只负责事件推送
+pub(super) fn start(bot: Arc<Bot>, host: String, port: u16) -> Result<Server, Error> {
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(bot.clone()))
+            .route("/", web::get().to(handle_event))
+            .route("/event", web::get().to(handle_event))
+    })
+        .bind((host, port))?
+        .run();
+    Ok(server)
+}
+
+async fn handle_event(req: HttpRequest, body: web::Payload, bot: web::Data<Arc<Bot>>) -> actix_web::Result<HttpResponse> {
+    let (response, mut session, mut stream) = actix_ws::handle(&req, body)?;
+    let bot_id = bot.unique_id;
+    let mut events = bot.subscribe();
+    info!("OneBot websocket connected: {:?}", req.peer_addr());
+    actix_web::rt::spawn(async move {
+        let _ = session.text(lifecycle_connect(bot_id).to_string()).await;
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => if let Some(data) = encode_event(&bot, &event).await {
+                        if session.text(data.to_string()).await.is_err() {
+                            break;
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => warn!("OneBot websocket lagged, {} events dropped", n),
+                    Err(RecvError::Closed) => break,
+                },
+                msg = stream.next() => match msg {
+                    Some(Ok(Message::Ping(bytes))) if session.pong(&bytes).await.is_err() => break,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                }
+            }
+        }
+        let _ = session.close(None).await;
+        info!("OneBot websocket disconnected");
+    });
+    Ok(response)
+}